@@ -1,27 +1,35 @@
 // This application observes the order book api and tries to determine if the
 // solver is down. It does this by checking if no trades have been made recently
 // and if so checking if it finds a matchable order according to an external
-// price api (0x). If this is the case it alerts.
+// price api (0x) and/or the order book's own quote api. If this is the case it
+// alerts.
 
 use {
     anyhow::{Context, Result},
     chrono::{DateTime, Utc},
     clap::Parser,
-    model::order::{OrderClass, OrderKind, OrderStatus, OrderUid, BUY_ETH_ADDRESS},
-    number::serialization::HexOrDecimalU256,
+    futures::{FutureExt, StreamExt, TryStreamExt},
+    model::{
+        order::{OrderClass, OrderKind, OrderStatus, OrderUid, BUY_ETH_ADDRESS},
+        quote::{OrderQuoteRequest, OrderQuoteResponse, OrderQuoteSide, SellAmount},
+    },
+    number::{nonzero::U256 as NonZeroU256, serialization::HexOrDecimalU256},
     primitive_types::{H160, U256},
-    prometheus::IntGauge,
+    prometheus::{IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts},
     reqwest::Client,
     serde_with::serde_as,
     std::{
         collections::HashMap,
+        future::Future,
+        str::FromStr,
         time::{Duration, Instant},
     },
+    tracing::Instrument,
     url::Url,
 };
 
 #[serde_as]
-#[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 struct Order {
     kind: OrderKind,
@@ -36,6 +44,7 @@ struct Order {
     uid: OrderUid,
     status: OrderStatus,
     creation_date: DateTime<Utc>,
+    valid_to: u32,
     partially_fillable: bool,
     #[serde(flatten)]
     class: OrderClass,
@@ -45,6 +54,61 @@ impl Order {
     fn is_liquidity_order(&self) -> bool {
         matches!(self.class, OrderClass::Liquidity)
     }
+
+    /// Expired orders can never be settled so they are not matchable.
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        i64::from(self.valid_to) < now.timestamp()
+    }
+}
+
+#[serde_as]
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Trade {
+    #[serde_as(as = "HexOrDecimalU256")]
+    buy_amount: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    sell_amount_before_fees: U256,
+}
+
+/// An order together with how much of it has already been executed on-chain.
+#[serde_as]
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderWithFills {
+    order: Order,
+    /// The executed amount in the order's sell token (for sell orders) or buy
+    /// token (for buy orders), excluding fees.
+    #[serde_as(as = "HexOrDecimalU256")]
+    executed_amount: U256,
+    /// How much of the order can still be filled, denominated like
+    /// `executed_amount`.
+    #[serde_as(as = "HexOrDecimalU256")]
+    remaining_amount: U256,
+}
+
+impl OrderWithFills {
+    fn new(order: Order, trades: &[Trade]) -> Self {
+        let (executed_amount, total_amount) = match order.kind {
+            OrderKind::Sell => (
+                trades.iter().fold(U256::zero(), |acc, trade| {
+                    acc.saturating_add(trade.sell_amount_before_fees)
+                }),
+                order.sell_amount,
+            ),
+            OrderKind::Buy => (
+                trades.iter().fold(U256::zero(), |acc, trade| {
+                    acc.saturating_add(trade.buy_amount)
+                }),
+                order.buy_amount,
+            ),
+        };
+        Self {
+            order,
+            executed_amount,
+            remaining_amount: total_amount.saturating_sub(executed_amount),
+        }
+    }
 }
 
 struct OrderBookApi {
@@ -87,14 +151,92 @@ impl OrderBookApi {
             .json()
             .await
     }
+
+    /// Fetches the order and its trades to determine how much of it is still
+    /// fillable. This costs an additional request so the update loop only
+    /// uses [`OrderBookApi::order`].
+    pub async fn order_with_fills(&self, uid: &OrderUid) -> reqwest::Result<OrderWithFills> {
+        let order = self.order(uid).await?;
+        let mut url = shared::url::join(&self.base, "api/v1/trades");
+        url.query_pairs_mut()
+            .append_pair("orderUid", &uid.to_string());
+        let trades: Vec<Trade> = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(OrderWithFills::new(order, &trades))
+    }
+
+    /// The price of one atom of the token denominated in the native token.
+    pub async fn native_price(&self, token: H160) -> reqwest::Result<f64> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            price: f64,
+        }
+
+        let url = shared::url::join(&self.base, &format!("api/v1/token/{token:#x}/native_price"));
+        let response: Response = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.price)
+    }
+
+    /// Checks whether the order can be settled according to a quote of the
+    /// order book for the order's token pair and amount.
+    pub async fn can_be_settled(&self, order: &Order) -> Result<bool> {
+        let side = match order.kind {
+            OrderKind::Buy => OrderQuoteSide::Buy {
+                buy_amount_after_fee: NonZeroU256::new(order.buy_amount)
+                    .context("zero buy amount")?,
+            },
+            OrderKind::Sell => OrderQuoteSide::Sell {
+                sell_amount: SellAmount::AfterFee {
+                    value: NonZeroU256::new(order.sell_amount).context("zero sell amount")?,
+                },
+            },
+        };
+        let request = OrderQuoteRequest {
+            sell_token: order.sell_token,
+            buy_token: order.buy_token,
+            side,
+            ..Default::default()
+        };
+
+        let url = shared::url::join(&self.base, "api/v1/quote");
+        let response: OrderQuoteResponse = self
+            .client
+            .post(url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        tracing::debug!(?response, "quote");
+
+        let can_settle = response.quote.sell_amount <= order.sell_amount
+            && response.quote.buy_amount >= order.buy_amount;
+        if can_settle {
+            tracing::debug!(%order.uid, "marking order as settleable according to quote");
+        }
+
+        Ok(can_settle)
+    }
 }
 
 // Converts the eth placeholder address to weth. Leaves other addresses
 // untouched.
-fn convert_eth_to_weth(token: H160) -> H160 {
-    let weth: H160 = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
-        .parse()
-        .unwrap();
+fn convert_eth_to_weth(token: H160, weth: H160) -> H160 {
     if token == BUY_ETH_ADDRESS {
         weth
     } else {
@@ -102,16 +244,40 @@ fn convert_eth_to_weth(token: H160) -> H160 {
     }
 }
 
+/// Where to find the 0x api of a chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ZeroExConfig {
+    base: Url,
+    /// The wrapped native token (e.g. WETH) of the chain which 0x needs
+    /// instead of the native token placeholder address.
+    weth: H160,
+}
+
 struct ZeroExApi {
     base: Url,
+    weth: H160,
     client: Client,
+    // Prices that are better than the order's limit price by more than this
+    // factor are considered erroneous.
+    max_price_deviation: f64,
+    // By how many basis points prices must beat the order's limit price for
+    // the order to be considered matchable.
+    margin_bps: u32,
 }
 
 impl ZeroExApi {
-    pub fn new(client: Client) -> Self {
+    pub fn new(
+        client: Client,
+        config: ZeroExConfig,
+        max_price_deviation: f64,
+        margin_bps: u32,
+    ) -> Self {
         Self {
-            base: "https://api.0x.org".parse().unwrap(),
+            base: config.base,
+            weth: config.weth,
             client,
+            max_price_deviation,
+            margin_bps,
         }
     }
 
@@ -123,7 +289,7 @@ impl ZeroExApi {
             OrderKind::Sell => ("sellAmount", order.sell_amount),
         };
 
-        let buy_token = convert_eth_to_weth(order.buy_token);
+        let buy_token = convert_eth_to_weth(order.buy_token, self.weth);
         url.query_pairs_mut()
             .append_pair("sellToken", &format!("{:#x}", order.sell_token))
             .append_pair("buyToken", &format!("{buy_token:#x}"))
@@ -150,8 +316,23 @@ impl ZeroExApi {
 
         tracing::debug!(url = url.as_str(), ?response, "0x");
 
-        let can_settle =
-            response.sell_amount <= order.sell_amount && response.buy_amount >= order.buy_amount;
+        let price_deviation = price_deviation(order, response.sell_amount, response.buy_amount);
+        if price_deviation.is_nan() || price_deviation > self.max_price_deviation {
+            tracing::warn!(
+                %order.uid,
+                ?response,
+                price_deviation,
+                "ignoring 0x price that deviates too much from the order's limit price"
+            );
+            return Ok(false);
+        }
+
+        let can_settle = beats_limit_price(
+            order,
+            response.sell_amount,
+            response.buy_amount,
+            self.margin_bps,
+        );
         if can_settle {
             tracing::debug!(%order.uid, "marking order as settleable");
         }
@@ -160,9 +341,100 @@ impl ZeroExApi {
     }
 }
 
+/// Compares the buy amount per sell amount of a price and the order's limit
+/// price. Values above 1 mean that the price is better than the limit price.
+fn price_deviation(order: &Order, sell_amount: U256, buy_amount: U256) -> f64 {
+    (buy_amount.to_f64_lossy() * order.sell_amount.to_f64_lossy())
+        / (sell_amount.to_f64_lossy() * order.buy_amount.to_f64_lossy())
+}
+
+/// Whether the order can be settled at the price and the price beats the
+/// order's limit price by at least `margin_bps` basis points. The margin
+/// accounts for the costs of settling the order which the price doesn't
+/// include.
+fn beats_limit_price(order: &Order, sell_amount: U256, buy_amount: U256, margin_bps: u32) -> bool {
+    let within_limit = sell_amount <= order.sell_amount && buy_amount >= order.buy_amount;
+    if !within_limit || margin_bps == 0 {
+        return within_limit;
+    }
+    let margin = 1. + f64::from(margin_bps) / 10_000.;
+    price_deviation(order, sell_amount, buy_amount) >= margin
+}
+
+/// How log lines get formatted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+enum LogFormat {
+    /// Human readable text.
+    Text,
+    /// One JSON object per line with all fields as separate keys.
+    Json,
+}
+
+/// Which price sources get used to determine whether an order is matchable.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+enum MatchabilitySource {
+    /// Only use 0x.
+    ZeroEx,
+    /// Only use the order book's quote endpoint.
+    Quote,
+    /// Use 0x and fall back to the order book's quote endpoint if 0x is
+    /// unavailable.
+    Fallback,
+    /// An order is only matchable if 0x and the order book's quote endpoint
+    /// agree.
+    Both,
+}
+
+impl MatchabilitySource {
+    fn uses_zeroex(self) -> bool {
+        !matches!(self, Self::Quote)
+    }
+}
+
+struct MatchabilityChecker {
+    source: MatchabilitySource,
+    // Only `None` if the source doesn't use 0x.
+    zeroex_api: Option<ZeroExApi>,
+    orderbook_api: OrderBookApi,
+    // How many orders get checked at the same time.
+    concurrency: usize,
+}
+
+impl MatchabilityChecker {
+    fn zeroex_api(&self) -> Result<&ZeroExApi> {
+        self.zeroex_api
+            .as_ref()
+            .context("0x is not available for this environment")
+    }
+
+    async fn can_be_settled(&self, order: &Order) -> Result<bool> {
+        match self.source {
+            MatchabilitySource::ZeroEx => self.zeroex_api()?.can_be_settled(order).await,
+            MatchabilitySource::Quote => self.orderbook_api.can_be_settled(order).await,
+            MatchabilitySource::Fallback => match self.zeroex_api()?.can_be_settled(order).await {
+                Ok(can_settle) => Ok(can_settle),
+                Err(err) => {
+                    tracing::warn!(?err, "0x unavailable, falling back to quote");
+                    self.orderbook_api.can_be_settled(order).await
+                }
+            },
+            MatchabilitySource::Both => {
+                let (zeroex, quote) = futures::try_join!(
+                    self.zeroex_api()?.can_be_settled(order),
+                    self.orderbook_api.can_be_settled(order),
+                )?;
+                if zeroex != quote {
+                    tracing::debug!(%order.uid, zeroex, quote, "price sources disagree");
+                }
+                Ok(zeroex && quote)
+            }
+        }
+    }
+}
+
 struct Alerter {
     orderbook_api: OrderBookApi,
-    zeroex_api: ZeroExApi,
+    matchability: MatchabilityChecker,
     config: AlertConfig,
     last_observed_trade: Instant,
     last_alert: Option<Instant>,
@@ -174,6 +446,14 @@ struct Alerter {
     // hasn't been a trade for some time and that there is an order that has been matchable for
     // some time.
     no_trades_but_matchable_order: IntGauge,
+    // How long the order that has been matchable the longest has been matchable for.
+    longest_matchable_order_seconds: IntGauge,
+    // When the alert condition started being satisfied.
+    alert_condition_since: Option<Instant>,
+    // Counts how often the alert condition stopped being satisfied.
+    alerts_resolved: IntCounter,
+    // Set to 1 while the alerter is running and to 0 once it shuts down.
+    ready: IntGauge,
     api_get_order_min_interval: Duration,
 }
 
@@ -184,33 +464,136 @@ struct AlertConfig {
     min_order_solvable_time: Duration,
     // Do not alert more often than this.
     min_alert_interval: Duration,
+    // The price sources used for determining whether an order is matchable.
+    matchability_source: MatchabilitySource,
+    // Only log what would have been alerted without actually alerting.
+    dry_run: bool,
+    // Orders whose sell amount is worth less than this in the native token are ignored.
+    min_order_native_value: U256,
+}
+
+/// The alerter metrics. They are labelled by environment so that one process
+/// can watch several order books.
+struct Metrics {
+    no_trades_but_matchable_order: IntGaugeVec,
+    longest_matchable_order_seconds: IntGaugeVec,
+    alerts_resolved: IntCounterVec,
+    ready: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = observe::metrics::get_registry();
+        let labels = &["environment"];
+        let no_trades_but_matchable_order =
+            IntGaugeVec::new(Opts::new("no_trades_but_matchable_order", "0 or 1"), labels).unwrap();
+        registry
+            .register(Box::new(no_trades_but_matchable_order.clone()))
+            .unwrap();
+        let longest_matchable_order_seconds = IntGaugeVec::new(
+            Opts::new(
+                "longest_matchable_order_seconds",
+                "how long the longest matchable order has been matchable for",
+            ),
+            labels,
+        )
+        .unwrap();
+        registry
+            .register(Box::new(longest_matchable_order_seconds.clone()))
+            .unwrap();
+        let alerts_resolved = IntCounterVec::new(
+            Opts::new(
+                "alerts_resolved_total",
+                "number of times the alert condition stopped being satisfied",
+            ),
+            labels,
+        )
+        .unwrap();
+        registry
+            .register(Box::new(alerts_resolved.clone()))
+            .unwrap();
+        let ready = IntGaugeVec::new(
+            Opts::new("ready", "whether the alerter is running, 0 or 1"),
+            labels,
+        )
+        .unwrap();
+        registry.register(Box::new(ready.clone())).unwrap();
+        Self {
+            no_trades_but_matchable_order,
+            longest_matchable_order_seconds,
+            alerts_resolved,
+            ready,
+        }
+    }
 }
 
 impl Alerter {
     pub fn new(
         orderbook_api: OrderBookApi,
-        zeroex_api: ZeroExApi,
+        matchability: MatchabilityChecker,
         config: AlertConfig,
         api_get_order_min_interval: Duration,
+        metrics: &Metrics,
+        environment: &str,
     ) -> Self {
-        let registry = observe::metrics::get_registry();
-        let no_trades_but_matchable_order =
-            IntGauge::new("no_trades_but_matchable_order", "0 or 1").unwrap();
-        registry
-            .register(Box::new(no_trades_but_matchable_order.clone()))
-            .unwrap();
+        let labels = &[environment];
+        let ready = metrics.ready.with_label_values(labels);
+        ready.set(1);
         Self {
             orderbook_api,
-            zeroex_api,
+            matchability,
             config,
             last_observed_trade: Instant::now(),
             last_alert: None,
             open_orders: HashMap::new(),
-            no_trades_but_matchable_order,
+            no_trades_but_matchable_order: metrics
+                .no_trades_but_matchable_order
+                .with_label_values(labels),
+            longest_matchable_order_seconds: metrics
+                .longest_matchable_order_seconds
+                .with_label_values(labels),
+            alert_condition_since: None,
+            alerts_resolved: metrics.alerts_resolved.with_label_values(labels),
+            ready,
             api_get_order_min_interval,
         }
     }
 
+    /// Summarizes the state after the last update.
+    fn status(&self, environment: &str, error: Option<String>) -> Status {
+        Status {
+            environment: environment.to_owned(),
+            open_orders: self.open_orders.len(),
+            matchable_orders: self
+                .open_orders
+                .values()
+                .filter(|(_, matchable_since)| matchable_since.is_some())
+                .count(),
+            alert_condition: self.alert_condition_since.is_some(),
+            error,
+        }
+    }
+
+    /// Marks the alerter as no longer ready.
+    pub fn shutdown(&self) {
+        self.ready.set(0);
+    }
+
+    /// Updates the alert condition metric and keeps track of how long the
+    /// condition was satisfied once it gets resolved.
+    fn set_alert_condition(&mut self, satisfied: bool) {
+        self.no_trades_but_matchable_order.set(satisfied.into());
+        match (satisfied, self.alert_condition_since) {
+            (true, None) => self.alert_condition_since = Some(Instant::now()),
+            (false, Some(since)) => {
+                self.alert_condition_since = None;
+                self.alerts_resolved.inc();
+                tracing::info!(duration = ?since.elapsed(), "resolved");
+            }
+            _ => (),
+        }
+    }
+
     async fn update_open_orders(&mut self) -> Result<()> {
         let mut orders = self
             .orderbook_api
@@ -243,6 +626,8 @@ impl Alerter {
             tracing::debug!(order =% uid, "found closed order");
             let start = Instant::now();
             let api_order = self.orderbook_api.order(uid).await.context("get order")?;
+            // Partially fillable orders are never tracked so a closed order was
+            // either executed completely or not at all.
             if api_order.status == OrderStatus::Fulfilled {
                 tracing::debug!(
                     "updating last observed trade because order {} was fulfilled",
@@ -260,7 +645,7 @@ impl Alerter {
     pub async fn update(&mut self) -> Result<()> {
         self.update_open_orders().await?;
         if self.last_observed_trade.elapsed() <= self.config.time_without_trade {
-            self.no_trades_but_matchable_order.set(0);
+            self.set_alert_condition(false);
             // Delete all matchable timestamps.
             //
             // If we didn't do this what could happen is that first we mark an order as
@@ -275,19 +660,46 @@ impl Alerter {
             for (_, instant) in self.open_orders.values_mut() {
                 *instant = None;
             }
+            self.longest_matchable_order_seconds.set(0);
             return Ok(());
         }
 
+        // All orders get checked so that we know the longest matchable one even if
+        // we already found an order to alert about.
+        let (orderbook_api, matchability) = (&self.orderbook_api, &self.matchability);
+        let min_order_native_value = self.config.min_order_native_value;
+        let now = Utc::now();
+        let matchable = check_orders(
+            self.open_orders.values().map(|(order, _)| order),
+            matchability.concurrency,
+            |order| async move {
+                if order.is_expired(now) {
+                    tracing::debug!(%order.uid, "skipping expired order");
+                    return Ok(false);
+                }
+                if is_below_native_value(orderbook_api, order, min_order_native_value).await {
+                    tracing::debug!(%order.uid, "skipping order below minimum value");
+                    return Ok(false);
+                }
+                matchability.can_be_settled(order).await
+            },
+        )
+        .await
+        .context("can_be_settled")?;
+
+        // `None` if the alert condition should be left unchanged.
+        let mut alert_condition = Some(false);
+        let mut longest_matchable = Duration::ZERO;
+        let now = Instant::now();
         for (order, last_solvable) in self.open_orders.values_mut() {
-            let can_be_settled = self
-                .zeroex_api
-                .can_be_settled(order)
-                .await
-                .context("can_be_settled")?;
-            let now = Instant::now();
-            if can_be_settled {
-                let solvable_since = *last_solvable.get_or_insert(now);
-                if now.duration_since(solvable_since) > self.config.min_order_solvable_time {
+            if matchable[&order.uid] {
+                let solvable_for = now.duration_since(*last_solvable.get_or_insert(now));
+                longest_matchable = longest_matchable.max(solvable_for);
+                if alert_condition == Some(true) {
+                    continue;
+                }
+                alert_condition = None;
+                if solvable_for >= self.config.min_order_solvable_time {
                     let should_alert = match self.last_alert {
                         None => true,
                         Some(instant) => instant.elapsed() >= self.config.min_alert_interval,
@@ -296,27 +708,180 @@ impl Alerter {
                         self.last_alert = Some(now);
                         self.config.alert(order);
                     }
-                    self.no_trades_but_matchable_order.set(1);
+                    alert_condition = Some(true);
                 }
-                return Ok(());
             } else {
                 *last_solvable = None;
             }
         }
 
-        self.no_trades_but_matchable_order.set(0);
+        self.longest_matchable_order_seconds
+            .set(longest_matchable.as_secs().try_into().unwrap_or(i64::MAX));
+        if let Some(satisfied) = alert_condition {
+            self.set_alert_condition(satisfied);
+        }
         Ok(())
     }
 }
 
+/// The outcome of a single update, printed by `--once`.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Status {
+    environment: String,
+    open_orders: usize,
+    matchable_orders: usize,
+    alert_condition: bool,
+    error: Option<String>,
+}
+
+impl Status {
+    fn is_healthy(&self) -> bool {
+        self.error.is_none() && !self.alert_condition
+    }
+}
+
+/// The fills of an order in one environment, printed by `--probe-order`.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Probe {
+    environment: String,
+    order: Option<OrderWithFills>,
+    error: Option<String>,
+}
+
+/// Whether the order's sell amount is worth less than `min_value` in the native
+/// token. Orders whose value can't be determined are not considered below it.
+async fn is_below_native_value(
+    orderbook_api: &OrderBookApi,
+    order: &Order,
+    min_value: U256,
+) -> bool {
+    if min_value.is_zero() {
+        return false;
+    }
+    match orderbook_api.native_price(order.sell_token).await {
+        Ok(price) => order.sell_amount.to_f64_lossy() * price < min_value.to_f64_lossy(),
+        Err(err) => {
+            tracing::warn!(?err, %order.uid, "failed to get native price");
+            false
+        }
+    }
+}
+
+/// Runs `check` for all orders with at most `concurrency` checks in flight at
+/// the same time and returns the result for each order.
+async fn check_orders<'a, Fut>(
+    orders: impl Iterator<Item = &'a Order>,
+    concurrency: usize,
+    check: impl Fn(&'a Order) -> Fut,
+) -> Result<HashMap<OrderUid, bool>>
+where
+    Fut: Future<Output = Result<bool>>,
+{
+    futures::stream::iter(orders)
+        .map(|order| {
+            let check = check(order);
+            async move { Ok((order.uid, check.await?)) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .try_collect()
+        .await
+}
+
 impl AlertConfig {
     fn alert(&self, order: &Order) {
-        tracing::error!(
-            "No orders have been settled in the last {} seconds even though order {} is solvable \
-             and has a price that allows it to be settled according to 0x.",
-            self.time_without_trade.as_secs(),
-            order.uid,
-        );
+        let time_without_trade_seconds = self.time_without_trade.as_secs();
+        let matchability_source = self.matchability_source;
+        if self.dry_run {
+            tracing::info!(
+                %order.uid,
+                ?order.sell_token,
+                ?order.buy_token,
+                time_without_trade_seconds,
+                ?matchability_source,
+                "[DRY RUN] No orders have been settled recently even though an order is solvable \
+                 and has a price that allows it to be settled."
+            );
+        } else {
+            tracing::error!(
+                %order.uid,
+                ?order.sell_token,
+                ?order.buy_token,
+                time_without_trade_seconds,
+                ?matchability_source,
+                "No orders have been settled recently even though an order is solvable and has a \
+                 price that allows it to be settled."
+            );
+        }
+    }
+}
+
+/// An order book the alerter watches, like mainnet or Gnosis Chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Environment {
+    /// The name used to label the metrics and logs of this environment.
+    name: String,
+    url: Url,
+    /// Overrides `--matchability-source` for this environment.
+    matchability_source: Option<MatchabilitySource>,
+}
+
+impl Environment {
+    /// Name of environments that are configured with a plain url.
+    const UNNAMED: &'static str = "default";
+
+    /// The 0x api of the environment's chain which is determined by the
+    /// environment's name. Unnamed environments are assumed to be mainnet.
+    /// `None` for chains the alerter doesn't know a 0x api for.
+    fn zeroex(&self) -> Option<ZeroExConfig> {
+        let (base, weth) = match self.name.as_str() {
+            "mainnet" | Self::UNNAMED => (
+                "https://api.0x.org",
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+            ),
+            "arbitrum" | "arbitrum_one" => (
+                "https://arbitrum.api.0x.org",
+                "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1",
+            ),
+            _ => return None,
+        };
+        Some(ZeroExConfig {
+            base: base.parse().unwrap(),
+            weth: weth.parse().unwrap(),
+        })
+    }
+}
+
+impl FromStr for Environment {
+    type Err = anyhow::Error;
+
+    /// Parses `name=url` optionally followed by `|source` to override the
+    /// matchability source. A plain url is accepted too and gets named
+    /// `default`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (environment, matchability_source) = match s.rsplit_once('|') {
+            Some((environment, source)) => (
+                environment,
+                Some(
+                    <MatchabilitySource as clap::ValueEnum>::from_str(source, false)
+                        .map_err(|err| anyhow::anyhow!("{err} in {s:?}"))?,
+                ),
+            ),
+            None => (s, None),
+        };
+        let (name, url) = match environment.split_once('=') {
+            Some((name, url)) if !name.contains("://") => (name, url),
+            _ => (Self::UNNAMED, environment),
+        };
+        anyhow::ensure!(!name.is_empty(), "empty environment name in {s:?}");
+        Ok(Self {
+            name: name.to_owned(),
+            url: url
+                .parse()
+                .with_context(|| format!("invalid url in {s:?}"))?,
+            matchability_source,
+        })
     }
 }
 
@@ -358,13 +923,51 @@ struct Arguments {
     )]
     min_alert_interval: Duration,
 
+    /// Which price sources are used for checking whether an order is
+    /// matchable.
+    #[clap(long, env, default_value = "zero-ex", value_enum)]
+    matchability_source: MatchabilitySource,
+
+    /// How many orders get checked for matchability at the same time.
+    #[clap(long, env, default_value = "1")]
+    matchability_concurrency: usize,
+
+    /// Orders whose sell amount is worth less than this many native tokens
+    /// (e.g. ETH) are not considered for alerting.
+    #[clap(long, env, default_value = "0", value_parser = shared::arguments::wei_from_ether)]
+    min_order_value: U256,
+
+    /// Ignore 0x prices that are better than the order's limit price by more
+    /// than this factor since they are likely stale or erroneous.
+    #[clap(long, env, default_value = "10")]
+    max_price_deviation: f64,
+
+    /// By how many basis points 0x prices must beat an order's limit price
+    /// for the order to count as matchable. Solvers have to cover the gas of
+    /// a settlement so orders that are only barely matchable don't get
+    /// settled in practice.
+    #[clap(long, env, default_value = "0")]
+    matchability_margin_bps: u32,
+
     /// How many errors in the update loop (fetching solvable orders or querying
-    /// 0x) in a row must happen before we alert about them.
+    /// prices) in a row must happen before we alert about them.
     #[clap(long, env, default_value = "5")]
     errors_in_a_row_before_alert: u32,
 
-    #[clap(long, env, default_value = "https://api.cow.fi/mainnet/")]
-    orderbook_api: String,
+    /// The order books to watch as a comma separated list of `name=url`
+    /// pairs. Every environment is monitored independently and its metrics
+    /// are labelled with its name. The name also determines the 0x api that
+    /// gets used, which is only known for `mainnet` (also used for a plain
+    /// url without a name) and `arbitrum_one`. Appending `|source`, e.g.
+    /// `gnosis=https://api.cow.fi/xdai/|quote`, overrides
+    /// `--matchability-source` for that environment.
+    #[clap(
+        long,
+        env,
+        default_value = "mainnet=https://api.cow.fi/mainnet/",
+        use_value_delimiter = true
+    )]
+    orderbook_api: Vec<Environment>,
 
     #[clap(long, env, default_value = "9588")]
     metrics_port: u16,
@@ -373,11 +976,51 @@ struct Arguments {
     /// can rate limit us.
     #[clap(long, env, default_value = "0.2", value_parser = shared::arguments::duration_from_seconds)]
     api_get_order_min_interval: Duration,
+
+    /// Only log the alerts that would have been raised instead of raising
+    /// them. Metrics are still updated.
+    #[clap(long, env)]
+    dry_run: bool,
+
+    /// Proxy used for outgoing HTTP requests. Also configurable with the
+    /// standard `HTTP_PROXY` environment variable.
+    #[clap(long, env)]
+    http_proxy: Option<Url>,
+
+    /// Proxy used for outgoing HTTPS requests. Also configurable with the
+    /// standard `HTTPS_PROXY` environment variable.
+    #[clap(long, env)]
+    https_proxy: Option<Url>,
+
+    /// Run a single update for every environment, print their status as JSON
+    /// and exit with a non-zero code if an update failed or the alert
+    /// condition is met. Since a single update has no history, use
+    /// `--time-without-trade 0 --min-order-age 0` to alert on any matchable
+    /// order.
+    #[clap(long, env)]
+    once: bool,
+
+    /// Instead of alerting, look up the order with this uid in every
+    /// environment, print how much of it has been executed as JSON and exit.
+    /// Exits with a non-zero code if no environment knows the order.
+    #[clap(long, env)]
+    probe_order: Option<OrderUid>,
+
+    /// Format of the log output.
+    #[clap(long, env, default_value = "text", value_enum)]
+    log_format: LogFormat,
 }
 
 pub async fn start(args: impl Iterator<Item = String>) {
     let args = Arguments::parse_from(args);
-    observe::tracing::initialize("alerter=debug", tracing::Level::ERROR.into());
+    match args.log_format {
+        LogFormat::Text => {
+            observe::tracing::initialize("alerter=debug", tracing::Level::ERROR.into())
+        }
+        LogFormat::Json => {
+            observe::tracing::initialize_json("alerter=debug", tracing::Level::ERROR.into())
+        }
+    }
     observe::panic_hook::install();
     observe::metrics::setup_registry(Some("gp_v2_alerter".to_string()), None);
     tracing::info!("running alerter with {:#?}", args);
@@ -385,24 +1028,121 @@ pub async fn start(args: impl Iterator<Item = String>) {
 }
 
 async fn run(args: Arguments) {
-    let filter = shared::metrics::handle_metrics();
-    tokio::task::spawn(warp::serve(filter).bind(([0, 0, 0, 0], args.metrics_port)));
+    if !args.once && args.probe_order.is_none() {
+        let filter = shared::metrics::handle_metrics();
+        tokio::task::spawn(warp::serve(filter).bind(([0, 0, 0, 0], args.metrics_port)));
+    }
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .unwrap();
+    let mut client = Client::builder().timeout(Duration::from_secs(10));
+    if let Some(proxy) = &args.http_proxy {
+        client = client.proxy(reqwest::Proxy::http(proxy.as_str()).expect("invalid HTTP proxy"));
+    }
+    if let Some(proxy) = &args.https_proxy {
+        client = client.proxy(reqwest::Proxy::https(proxy.as_str()).expect("invalid HTTPS proxy"));
+    }
+    let client = client.build().unwrap();
+
+    if let Some(uid) = &args.probe_order {
+        let probes = futures::future::join_all(args.orderbook_api.iter().map(|environment| {
+            let orderbook_api = OrderBookApi::new(client.clone(), environment.url.as_str());
+            async move {
+                let result = orderbook_api.order_with_fills(uid).await;
+                Probe {
+                    environment: environment.name.clone(),
+                    error: result.as_ref().err().map(|err| format!("{err:?}")),
+                    order: result.ok(),
+                }
+            }
+        }))
+        .await;
+        println!("{}", serde_json::to_string_pretty(&probes).unwrap());
+        let found = probes.iter().any(|probe| probe.order.is_some());
+        std::process::exit(if found { 0 } else { 1 });
+    }
+
+    for environment in &args.orderbook_api {
+        assert!(
+            !environment
+                .matchability_source
+                .unwrap_or(args.matchability_source)
+                .uses_zeroex()
+                || environment.zeroex().is_some(),
+            "0x is not available for environment {:?}, append |quote to its url",
+            environment.name
+        );
+    }
+
+    let metrics = Metrics::new();
+    let shutdown = shutdown_signal().shared();
+    let alerters = args.orderbook_api.iter().map(|environment| {
+        let span = tracing::info_span!("environment", name = %environment.name);
+        let matchability_source = environment
+            .matchability_source
+            .unwrap_or(args.matchability_source);
+        let zeroex_api = environment
+            .zeroex()
+            .filter(|_| matchability_source.uses_zeroex())
+            .map(|config| {
+                ZeroExApi::new(
+                    client.clone(),
+                    config,
+                    args.max_price_deviation,
+                    args.matchability_margin_bps,
+                )
+            });
+        let alerter = Alerter::new(
+            OrderBookApi::new(client.clone(), environment.url.as_str()),
+            MatchabilityChecker {
+                source: matchability_source,
+                zeroex_api,
+                orderbook_api: OrderBookApi::new(client.clone(), environment.url.as_str()),
+                concurrency: args.matchability_concurrency,
+            },
+            AlertConfig {
+                time_without_trade: args.time_without_trade,
+                min_order_solvable_time: args.min_order_age,
+                min_alert_interval: args.min_alert_interval,
+                matchability_source,
+                dry_run: args.dry_run,
+                min_order_native_value: args.min_order_value,
+            },
+            args.api_get_order_min_interval,
+            &metrics,
+            &environment.name,
+        );
+        (environment, alerter, span)
+    });
+
+    if args.once {
+        let statuses = futures::future::join_all(alerters.map(|(environment, alerter, span)| {
+            run_once(alerter, &environment.name).instrument(span)
+        }))
+        .await;
+        println!("{}", serde_json::to_string_pretty(&statuses).unwrap());
+        let healthy = statuses.iter().all(Status::is_healthy);
+        std::process::exit(if healthy { 0 } else { 1 });
+    }
 
-    let mut alerter = Alerter::new(
-        OrderBookApi::new(client.clone(), &args.orderbook_api),
-        ZeroExApi::new(client),
-        AlertConfig {
-            time_without_trade: args.time_without_trade,
-            min_order_solvable_time: args.min_order_age,
-            min_alert_interval: args.min_alert_interval,
-        },
-        args.api_get_order_min_interval,
-    );
+    futures::future::join_all(
+        alerters.map(|(_, alerter, span)| {
+            run_alerter(alerter, &args, shutdown.clone()).instrument(span)
+        }),
+    )
+    .await;
+
+    tracing::info!("shutting down");
+}
+
+/// Updates the alerter once and reports the outcome.
+async fn run_once(mut alerter: Alerter, environment: &str) -> Status {
+    let error = alerter.update().await.err().map(|err| format!("{err:?}"));
+    alerter.shutdown();
+    alerter.status(environment, error)
+}
+
+/// Updates the alerter in a loop until `shutdown` resolves.
+async fn run_alerter(mut alerter: Alerter, args: &Arguments, shutdown: impl Future<Output = ()>) {
+    futures::pin_mut!(shutdown);
 
     let mut errors_in_a_row = 0;
     loop {
@@ -417,6 +1157,238 @@ async fn run(args: Arguments) {
                 tracing::error!(?err, "alerter update error");
             }
         }
-        tokio::time::sleep(args.update_interval).await;
+        // Only check for the signal in between updates so that an update (and any
+        // alert it raises) is always completed.
+        tokio::select! {
+            _ = tokio::time::sleep(args.update_interval) => (),
+            _ = &mut shutdown => break,
+        }
+    }
+
+    alerter.shutdown();
+}
+
+#[cfg(unix)]
+fn shutdown_signal() -> impl Future<Output = ()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    // Register the handlers right away so that signals received during an update
+    // don't terminate the process. Kubernetes sends sigterm, whereas locally
+    // sigint (ctrl-c) is most common.
+    let mut interrupt = signal(SignalKind::interrupt()).unwrap();
+    let mut terminate = signal(SignalKind::terminate()).unwrap();
+    async move {
+        tokio::select! {
+            _ = interrupt.recv() => (),
+            _ = terminate.recv() => (),
+        };
+    }
+}
+
+#[cfg(windows)]
+fn shutdown_signal() -> impl Future<Output = ()> {
+    // We don't support signal handling on Windows.
+    std::future::pending()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    fn order(uid: u8) -> Order {
+        Order {
+            kind: OrderKind::Sell,
+            buy_token: H160([1; 20]),
+            buy_amount: 1.into(),
+            sell_token: H160([2; 20]),
+            sell_amount: 1.into(),
+            fee_amount: 0.into(),
+            uid: OrderUid([uid; 56]),
+            status: OrderStatus::Open,
+            creation_date: Default::default(),
+            valid_to: u32::MAX,
+            partially_fillable: false,
+            class: OrderClass::Market,
+        }
+    }
+
+    #[test]
+    fn order_expiry() {
+        let now = Utc::now();
+        let valid_to = |seconds: i64| Order {
+            valid_to: (now.timestamp() + seconds).try_into().unwrap(),
+            ..order(0)
+        };
+        assert!(valid_to(-1).is_expired(now));
+        assert!(!valid_to(0).is_expired(now));
+        assert!(!valid_to(1).is_expired(now));
+    }
+
+    #[test]
+    fn remaining_amount_of_partially_executed_orders() {
+        let trade = |sell_amount_before_fees: u64, buy_amount: u64| Trade {
+            buy_amount: buy_amount.into(),
+            sell_amount_before_fees: sell_amount_before_fees.into(),
+        };
+        let trades = [trade(300, 30), trade(200, 20)];
+
+        let sell = OrderWithFills::new(
+            Order {
+                sell_amount: 1000.into(),
+                ..order(0)
+            },
+            &trades,
+        );
+        assert_eq!(sell.executed_amount, 500.into());
+        assert_eq!(sell.remaining_amount, 500.into());
+
+        let buy = OrderWithFills::new(
+            Order {
+                kind: OrderKind::Buy,
+                buy_amount: 40.into(),
+                ..order(0)
+            },
+            &trades,
+        );
+        assert_eq!(buy.executed_amount, 50.into());
+        assert_eq!(buy.remaining_amount, 0.into());
+
+        let unfilled = OrderWithFills::new(order(0), &[]);
+        assert_eq!(unfilled.executed_amount, 0.into());
+        assert_eq!(unfilled.remaining_amount, 1.into());
+    }
+
+    #[test]
+    fn matchability_margin() {
+        let order = |kind| Order {
+            kind,
+            sell_amount: 1000.into(),
+            buy_amount: 1000.into(),
+            ..order(0)
+        };
+        let sell = order(OrderKind::Sell);
+        let buy = order(OrderKind::Buy);
+
+        assert!(beats_limit_price(&sell, 1000.into(), 1000.into(), 0));
+        assert!(!beats_limit_price(&sell, 1000.into(), 999.into(), 0));
+        assert!(!beats_limit_price(&buy, 1001.into(), 1000.into(), 0));
+
+        assert!(!beats_limit_price(&sell, 1000.into(), 1000.into(), 100));
+        assert!(!beats_limit_price(&sell, 1000.into(), 1009.into(), 100));
+        assert!(beats_limit_price(&sell, 1000.into(), 1011.into(), 100));
+        assert!(!beats_limit_price(&buy, 991.into(), 1000.into(), 100));
+        assert!(beats_limit_price(&buy, 989.into(), 1000.into(), 100));
+    }
+
+    #[test]
+    fn zeroex_api_depends_on_environment() {
+        let environment = |s: &str| s.parse::<Environment>().unwrap();
+
+        let mainnet = environment("mainnet=https://api.cow.fi/mainnet/")
+            .zeroex()
+            .unwrap();
+        assert_eq!(mainnet.base.as_str(), "https://api.0x.org/");
+        assert_eq!(
+            mainnet.weth,
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+                .parse::<H160>()
+                .unwrap()
+        );
+        let arbitrum = environment("arbitrum_one=https://api.cow.fi/arbitrum_one/")
+            .zeroex()
+            .unwrap();
+        assert_eq!(arbitrum.base.as_str(), "https://arbitrum.api.0x.org/");
+        assert_eq!(
+            arbitrum.weth,
+            "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1"
+                .parse::<H160>()
+                .unwrap()
+        );
+
+        assert!(environment("gnosis=https://api.cow.fi/xdai/")
+            .zeroex()
+            .is_none());
+        // unnamed environments are assumed to be mainnet
+        assert_eq!(
+            environment("https://api.cow.fi/mainnet/").zeroex(),
+            Some(mainnet)
+        );
+    }
+
+    #[test]
+    fn parses_environments() {
+        assert_eq!(
+            "gnosis=https://api.cow.fi/xdai/"
+                .parse::<Environment>()
+                .unwrap(),
+            Environment {
+                name: "gnosis".to_owned(),
+                url: "https://api.cow.fi/xdai/".parse().unwrap(),
+                matchability_source: None,
+            }
+        );
+        assert_eq!(
+            "gnosis=https://api.cow.fi/xdai/|quote"
+                .parse::<Environment>()
+                .unwrap(),
+            Environment {
+                name: "gnosis".to_owned(),
+                url: "https://api.cow.fi/xdai/".parse().unwrap(),
+                matchability_source: Some(MatchabilitySource::Quote),
+            }
+        );
+        assert_eq!(
+            "https://api.cow.fi/mainnet/?a=b"
+                .parse::<Environment>()
+                .unwrap(),
+            Environment {
+                name: "default".to_owned(),
+                url: "https://api.cow.fi/mainnet/?a=b".parse().unwrap(),
+                matchability_source: None,
+            }
+        );
+        assert_eq!(
+            "https://api.cow.fi/mainnet/|zero-ex"
+                .parse::<Environment>()
+                .unwrap()
+                .matchability_source,
+            Some(MatchabilitySource::ZeroEx)
+        );
+        assert!("gnosis=https://api.cow.fi/xdai/|unknown"
+            .parse::<Environment>()
+            .is_err());
+        assert!("=https://api.cow.fi/mainnet/"
+            .parse::<Environment>()
+            .is_err());
+        assert!("mainnet=not a url".parse::<Environment>().is_err());
+    }
+
+    #[tokio::test]
+    async fn checks_orders_concurrently() {
+        let orders: Vec<_> = (0..10).map(order).collect();
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        let matchable = check_orders(orders.iter(), 4, |order| {
+            let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(order.uid.0[0] % 2 == 0)
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 4);
+        assert_eq!(matchable.len(), orders.len());
+        for order in &orders {
+            assert_eq!(matchable[&order.uid], order.uid.0[0] % 2 == 0);
+        }
     }
 }
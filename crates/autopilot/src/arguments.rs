@@ -75,6 +75,11 @@ pub struct Arguments {
     )]
     pub token_quality_cache_expiry: Duration,
 
+    /// How many rounds of transfers into and out of the settlement contract
+    /// get simulated to detect tokens that only misbehave on later transfers.
+    #[clap(long, env, default_value = "1")]
+    pub token_quality_transfer_rounds: usize,
+
     /// The number of pairs that are automatically updated in the pool cache.
     #[clap(long, env, default_value = "200")]
     pub pool_cache_lru_size: NonZeroUsize,
@@ -203,6 +208,26 @@ pub struct Arguments {
         value_parser = shared::arguments::duration_from_seconds,
     )]
     pub solve_deadline: Duration,
+
+    /// Number of days order events are kept in the database before they get
+    /// deleted. If not set, order events are never deleted.
+    #[clap(long, env)]
+    pub order_events_retention_days: Option<u32>,
+
+    /// Skip storing an order event if the most recent event of the same order
+    /// has the same label. Disable this to keep the full trail of events.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "true")]
+    pub dedup_order_events: bool,
+
+    /// How long in seconds an order event is considered a duplicate of a
+    /// previous event with the same label.
+    #[clap(
+        long,
+        env,
+        default_value = "3600",
+        value_parser = shared::arguments::duration_from_seconds,
+    )]
+    pub order_events_dedup_window: Duration,
 }
 
 impl std::fmt::Display for Arguments {
@@ -229,6 +254,11 @@ impl std::fmt::Display for Arguments {
             "token_quality_cache_expiry: {:?}",
             self.token_quality_cache_expiry
         )?;
+        writeln!(
+            f,
+            "token_quality_transfer_rounds: {}",
+            self.token_quality_transfer_rounds
+        )?;
         writeln!(f, "pool_cache_lru_size: {}", self.pool_cache_lru_size)?;
         writeln!(
             f,
@@ -270,6 +300,17 @@ impl std::fmt::Display for Arguments {
         writeln!(f, "score_cap: {}", self.score_cap)?;
         display_option(f, "shadow", &self.shadow)?;
         writeln!(f, "solve_deadline: {:?}", self.solve_deadline)?;
+        writeln!(
+            f,
+            "order_events_retention_days: {:?}",
+            self.order_events_retention_days
+        )?;
+        writeln!(f, "dedup_order_events: {}", self.dedup_order_events)?;
+        writeln!(
+            f,
+            "order_events_dedup_window: {:?}",
+            self.order_events_dedup_window
+        )?;
         Ok(())
     }
 }
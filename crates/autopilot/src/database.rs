@@ -16,12 +16,26 @@ use {
     std::time::Duration,
 };
 
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// If set, an order event does not get stored when the most recent event
+    /// of the same order has the same label and was registered less than this
+    /// duration ago.
+    pub order_events_dedup_window: Option<chrono::Duration>,
+}
+
 #[derive(Debug, Clone)]
-pub struct Postgres(pub PgPool);
+pub struct Postgres {
+    pub pool: PgPool,
+    pub config: Config,
+}
 
 impl Postgres {
-    pub async fn new(url: &str) -> sqlx::Result<Self> {
-        Ok(Self(PgPool::connect(url).await?))
+    pub async fn new(url: &str, config: Config) -> sqlx::Result<Self> {
+        Ok(Self {
+            pool: PgPool::connect(url).await?,
+            config,
+        })
     }
 
     pub async fn update_database_metrics(&self) -> sqlx::Result<()> {
@@ -29,14 +43,14 @@ impl Postgres {
 
         // update table row metrics
         for &table in database::ALL_TABLES {
-            let mut ex = self.0.acquire().await?;
+            let mut ex = self.pool.acquire().await?;
             let count = count_rows_in_table(&mut ex, table).await?;
             metrics.table_rows.with_label_values(&[table]).set(count);
         }
 
         // update unused app data metric
         {
-            let mut ex = self.0.acquire().await?;
+            let mut ex = self.pool.acquire().await?;
             let count = count_unused_app_data(&mut ex).await?;
             metrics.unused_app_data.set(count);
         }
@@ -79,6 +93,10 @@ struct Metrics {
     /// Timing of db queries.
     #[metric(name = "autopilot_database_queries", labels("type"))]
     database_queries: prometheus::HistogramVec,
+
+    /// Number of order events inserted into the db.
+    #[metric(name = "order_events_inserted_total")]
+    order_events_inserted: prometheus::IntCounter,
 }
 
 impl Metrics {
@@ -103,8 +121,10 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn postgres_count_rows_in_table_() {
-        let db = Postgres::new("postgresql://").await.unwrap();
-        let mut ex = db.0.begin().await.unwrap();
+        let db = Postgres::new("postgresql://", Default::default())
+            .await
+            .unwrap();
+        let mut ex = db.pool.begin().await.unwrap();
         database::clear_DANGER_(&mut ex).await.unwrap();
 
         let count = count_rows_in_table(&mut ex, "orders").await.unwrap();
@@ -29,7 +29,7 @@ impl QuoteStoring for Postgres {
             .with_label_values(&["save_quote"])
             .start_timer();
 
-        let mut ex = self.0.acquire().await?;
+        let mut ex = self.pool.acquire().await?;
         let row = create_quote_row(data);
         let id = database::quotes::save(&mut ex, &row).await?;
         Ok(id)
@@ -41,7 +41,7 @@ impl QuoteStoring for Postgres {
             .with_label_values(&["get_quote"])
             .start_timer();
 
-        let mut ex = self.0.acquire().await?;
+        let mut ex = self.pool.acquire().await?;
         let quote = database::quotes::get(&mut ex, id).await?;
         quote.map(TryFrom::try_from).transpose()
     }
@@ -56,7 +56,7 @@ impl QuoteStoring for Postgres {
             .with_label_values(&["find_quote"])
             .start_timer();
 
-        let mut ex = self.0.acquire().await?;
+        let mut ex = self.pool.acquire().await?;
         let params = create_db_search_parameters(params, expiration);
         let quote = database::quotes::find(&mut ex, &params)
             .await
@@ -74,7 +74,7 @@ impl Postgres {
             .with_label_values(&["solvable_orders"])
             .start_timer();
 
-        let mut ex = self.0.begin().await?;
+        let mut ex = self.pool.begin().await?;
         // Set the transaction isolation level to REPEATABLE READ
         // so the both SELECT queries below are executed in the same database snapshot
         // taken at the moment before the first query is executed.
@@ -103,7 +103,7 @@ impl Postgres {
             .start_timer();
 
         let data = serde_json::to_value(auction)?;
-        let mut ex = self.0.begin().await?;
+        let mut ex = self.pool.begin().await?;
         database::auction::delete_all_auctions(&mut ex).await?;
         let id = database::auction::save(&mut ex, &data).await?;
         ex.commit().await?;
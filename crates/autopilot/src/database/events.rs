@@ -52,7 +52,7 @@ impl EventStoring<ContractEvent> for Postgres {
             .with_label_values(&["last_event_block"])
             .start_timer();
 
-        let mut con = self.0.acquire().await?;
+        let mut con = self.pool.acquire().await?;
         let block_number = database::events::last_block(&mut con)
             .await
             .context("block_number_of_most_recent_event failed")?;
@@ -66,7 +66,7 @@ impl EventStoring<ContractEvent> for Postgres {
             .start_timer();
 
         let events = contract_to_db_events(events)?;
-        let mut transaction = self.0.begin().await?;
+        let mut transaction = self.pool.begin().await?;
         database::events::append(&mut transaction, &events)
             .await
             .context("append_events")?;
@@ -85,7 +85,7 @@ impl EventStoring<ContractEvent> for Postgres {
             .start_timer();
 
         let events = contract_to_db_events(events)?;
-        let mut transaction = self.0.begin().await?;
+        let mut transaction = self.pool.begin().await?;
         database::events::delete(&mut transaction, *range.start() as i64)
             .await
             .context("delete_events failed")?;
@@ -174,7 +174,7 @@ impl<T: Sync + Send + Clone, W: Sync + Send + Clone> EventStoring<ContractEvent>
             .with_label_values(&["replace_onchain_order_events"])
             .start_timer();
 
-        let mut transaction = self.db.0.begin().await?;
+        let mut transaction = self.db.pool.begin().await?;
 
         database::onchain_broadcasted_orders::mark_as_reorged(
             &mut transaction,
@@ -252,7 +252,7 @@ impl<T: Sync + Send + Clone, W: Sync + Send + Clone> EventStoring<ContractEvent>
             .database_queries
             .with_label_values(&["append_onchain_order_events"])
             .start_timer();
-        let mut transaction = self.db.0.begin().await?;
+        let mut transaction = self.db.pool.begin().await?;
 
         database::onchain_invalidations::insert_onchain_invalidations(
             &mut transaction,
@@ -296,7 +296,7 @@ impl<T: Sync + Send + Clone, W: Sync + Send + Clone> EventStoring<ContractEvent>
             .with_label_values(&["last_event_block"])
             .start_timer();
 
-        let mut con = self.db.0.acquire().await?;
+        let mut con = self.db.pool.acquire().await?;
         let block_number = database::onchain_broadcasted_orders::last_block(&mut con)
             .await
             .context("block_number_of_most_recent_event failed")?;
@@ -1372,7 +1372,10 @@ mod test {
             .returning(|_, _, _, _| 1u8);
         let web3 = Web3::new(create_env_test_transport());
         let onchain_order_parser = OnchainOrderParser {
-            db: Postgres(PgPool::connect_lazy("postgresql://").unwrap()),
+            db: Postgres {
+                pool: PgPool::connect_lazy("postgresql://").unwrap(),
+                config: Default::default(),
+            },
             web3,
             quoter: Arc::new(order_quoter),
             custom_onchain_data_parser: Box::new(custom_onchain_order_parser),
@@ -1,41 +1,151 @@
 pub use database::order_events::OrderEventLabel;
 use {
     anyhow::{Context, Result},
-    chrono::{DateTime, Utc},
+    chrono::{DateTime, Duration, Utc},
     database::{
         byte_array::ByteArray,
         order_events::{self, OrderEvent},
     },
     model::order::OrderUid,
+    std::collections::HashMap,
 };
 
 impl super::Postgres {
     /// Inserts the given events with the current timestamp into the DB.
+    /// Events that would repeat the most recent event of an order get skipped
+    /// if deduplication is configured.
     /// If this function encounters an error it will only be printed. More
     /// elaborate error handling is not necessary because this is just
     /// debugging information.
     pub async fn store_order_events(&self, events: &[(OrderUid, OrderEventLabel)]) {
-        if let Err(err) = store_order_events(self, events, Utc::now()).await {
-            tracing::warn!(?err, "failed to insert order events");
+        match store_order_events(self, events, Utc::now()).await {
+            Ok(inserted) => super::Metrics::get()
+                .order_events_inserted
+                .inc_by(inserted as u64),
+            Err(err) => tracing::warn!(?err, "failed to insert order events"),
         }
     }
+
+    /// Deletes all events registered before the given timestamp and returns
+    /// how many were deleted.
+    pub async fn prune_order_events(&self, older_than: DateTime<Utc>) -> Result<u64> {
+        let _timer = super::Metrics::get()
+            .database_queries
+            .with_label_values(&["prune_order_events"])
+            .start_timer();
+
+        let mut ex = self.pool.acquire().await?;
+        Ok(order_events::delete_order_events_before(&mut ex, older_than).await?)
+    }
+
+    /// Returns all events of an order from oldest to newest.
+    pub async fn order_events(&self, uid: &OrderUid) -> Result<Vec<OrderEvent>> {
+        let _timer = super::Metrics::get()
+            .database_queries
+            .with_label_values(&["order_events"])
+            .start_timer();
+
+        let mut ex = self.pool.acquire().await?;
+        Ok(order_events::fetch_order_events(&mut ex, &ByteArray(uid.0)).await?)
+    }
+
+    /// Returns the most recent event of an order.
+    pub async fn latest_order_event(&self, uid: &OrderUid) -> Result<Option<OrderEvent>> {
+        let _timer = super::Metrics::get()
+            .database_queries
+            .with_label_values(&["latest_order_event"])
+            .start_timer();
+
+        let mut ex = self.pool.acquire().await?;
+        Ok(order_events::fetch_latest_order_event(&mut ex, &ByteArray(uid.0)).await?)
+    }
+}
+
+/// Periodically deletes order events that are older than the retention
+/// period. The events are only used for debugging and computing service level
+/// indicators so old ones are not needed anymore.
+pub async fn order_events_cleanup(db: super::Postgres, retention: Duration) -> ! {
+    loop {
+        let older_than = Utc::now() - retention;
+        match db.prune_order_events(older_than).await {
+            Ok(deleted) => tracing::debug!(deleted, "pruned old order events"),
+            Err(err) => tracing::warn!(?err, "failed to prune old order events"),
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(60 * 60)).await;
+    }
 }
 
 async fn store_order_events(
     db: &super::Postgres,
     events: &[(OrderUid, OrderEventLabel)],
     timestamp: DateTime<Utc>,
-) -> Result<()> {
-    let mut ex = db.0.begin().await.context("begin transaction")?;
-    for (uid, label) in events {
-        let event = OrderEvent {
+) -> Result<usize> {
+    let mut events: Vec<_> = events
+        .iter()
+        .map(|(uid, label)| OrderEvent {
             order_uid: ByteArray(uid.0),
             timestamp,
             label: *label,
+        })
+        .collect();
+    let mut ex = db.pool.begin().await.context("begin transaction")?;
+    if let Some(window) = db.config.order_events_dedup_window {
+        let uids: Vec<_> = events.iter().map(|event| event.order_uid).collect();
+        let latest: HashMap<_, _> = order_events::fetch_latest_order_events(&mut ex, &uids)
+            .await
+            .context("fetch latest events")?
+            .into_iter()
+            .map(|event| (event.order_uid, event))
+            .collect();
+        events.retain(|event| !is_duplicate(latest.get(&event.order_uid), event, window));
+    }
+    order_events::insert_order_events(&mut ex, &events).await?;
+    ex.commit().await?;
+    Ok(events.len())
+}
+
+/// Checks whether the event only repeats the latest stored event of the same
+/// order within the deduplication window.
+fn is_duplicate(latest: Option<&OrderEvent>, event: &OrderEvent, window: Duration) -> bool {
+    latest.is_some_and(|latest| {
+        latest.label == event.label && event.timestamp - latest.timestamp < window
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_duplicate_events() {
+        let now = Utc::now();
+        let event = |label, timestamp| OrderEvent {
+            order_uid: ByteArray([1; 56]),
+            timestamp,
+            label,
         };
+        let window = Duration::minutes(10);
+        let latest = event(OrderEventLabel::Ready, now - Duration::minutes(5));
 
-        order_events::insert_order_event(&mut ex, &event).await?
+        assert!(is_duplicate(
+            Some(&latest),
+            &event(OrderEventLabel::Ready, now),
+            window
+        ));
+        assert!(!is_duplicate(
+            Some(&latest),
+            &event(OrderEventLabel::Filtered, now),
+            window
+        ));
+        assert!(!is_duplicate(
+            Some(&latest),
+            &event(OrderEventLabel::Ready, now + Duration::minutes(10)),
+            window
+        ));
+        assert!(!is_duplicate(
+            None,
+            &event(OrderEventLabel::Ready, now),
+            window
+        ));
     }
-    ex.commit().await?;
-    Ok(())
 }
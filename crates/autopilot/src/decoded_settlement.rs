@@ -8,8 +8,8 @@ use {
     database::orders::OrderClass,
     ethcontract::{common::FunctionExt, tokens::Tokenize, Address, Bytes, H160, U256},
     model::{
-        order::{OrderKind, OrderUid},
-        signature::Signature,
+        order::{BuyTokenDestination, OrderKind, OrderUid, SellTokenSource},
+        signature::{Signature, SigningScheme},
     },
     num::BigRational,
     number::conversions::{big_decimal_to_u256, big_rational_to_u256, u256_to_big_rational},
@@ -18,7 +18,7 @@ use {
         db_order_conversions::signing_scheme_from,
         external_prices::ExternalPrices,
     },
-    web3::ethabi::{Function, Token},
+    web3::ethabi::{Contract, Function, Token},
 };
 
 // Original type for input of `GPv2Settlement.settle` function.
@@ -47,6 +47,9 @@ pub struct DecodedSettlement {
     pub tokens: Vec<Address>,
     pub clearing_prices: Vec<U256>,
     pub trades: Vec<DecodedTrade>,
+    /// Interactions executed before, during and after the trades. Prefer the
+    /// named accessors like [`DecodedSettlement::pre_interactions`] over
+    /// indexing into this array.
     pub interactions: [Vec<DecodedInteraction>; 3],
     /// Data that was appended to the regular call data of the `settle()` call
     /// as a form of on-chain meta data. This gets used to associated a
@@ -54,6 +57,24 @@ pub struct DecodedSettlement {
     pub metadata: Option<Bytes<[u8; Self::META_DATA_LEN]>>,
 }
 
+/// Summary statistics of a settlement, e.g. for analyzing its costs.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SettlementStats {
+    pub trades: usize,
+    /// The number of interactions across all stages.
+    pub interactions: usize,
+    /// The length of the `settle()` calldata including the metadata.
+    pub calldata_len: usize,
+}
+
+/// The amounts a trade of a settlement actually transfers. The sell amount
+/// doesn't include the fee.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExecutedAmounts {
+    pub sell: U256,
+    pub buy: U256,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct DecodedTrade {
     pub sell_token_index: U256,
@@ -83,6 +104,19 @@ impl DecodedTrade {
     }
 }
 
+/// Encodes the arguments of `GPv2Settlement::settle` into its calldata.
+fn encode_settlement(settlement: DecodedSettlementTokenized) -> Vec<u8> {
+    let Token::Tuple(tokens) = settlement.into_token() else {
+        unreachable!("tuples get tokenized into tuples")
+    };
+    GPv2Settlement::raw_contract()
+        .abi
+        .function("settle")
+        .unwrap()
+        .encode_input(&tokens)
+        .expect("tokens match the settle() signature")
+}
+
 /// Trade flags are encoded in a 256-bit integer field. For more information on
 /// how flags are encoded see:
 /// <https://github.com/cowprotocol/contracts/blob/v1.0.0/src/contracts/libraries/GPv2Trade.sol#L58-L94>
@@ -90,11 +124,43 @@ impl DecodedTrade {
 pub struct TradeFlags(pub U256);
 
 impl TradeFlags {
+    /// Encodes the flags the same way the settlement contract expects them.
+    pub fn encode(
+        kind: OrderKind,
+        partially_fillable: bool,
+        sell_token_balance: SellTokenSource,
+        buy_token_balance: BuyTokenDestination,
+        signing_scheme: SigningScheme,
+    ) -> U256 {
+        let mut result = 0u8;
+        result |= match kind {
+            OrderKind::Sell => 0b0,
+            OrderKind::Buy => 0b1,
+        };
+        result |= (partially_fillable as u8) << 1;
+        result |= match sell_token_balance {
+            SellTokenSource::Erc20 => 0b00,
+            SellTokenSource::External => 0b10,
+            SellTokenSource::Internal => 0b11,
+        } << 2;
+        result |= match buy_token_balance {
+            BuyTokenDestination::Erc20 => 0b0,
+            BuyTokenDestination::Internal => 0b1,
+        } << 4;
+        result |= match signing_scheme {
+            SigningScheme::Eip712 => 0b00,
+            SigningScheme::EthSign => 0b01,
+            SigningScheme::Eip1271 => 0b10,
+            SigningScheme::PreSign => 0b11,
+        } << 5;
+        result.into()
+    }
+
     fn as_u8(&self) -> u8 {
         self.0.byte(0)
     }
 
-    fn order_kind(&self) -> OrderKind {
+    pub fn order_kind(&self) -> OrderKind {
         if self.as_u8() & 0b1 == 0 {
             OrderKind::Sell
         } else {
@@ -102,9 +168,39 @@ impl TradeFlags {
         }
     }
 
-    fn partially_fillable(&self) -> bool {
+    pub fn partially_fillable(&self) -> bool {
         self.as_u8() & 0b10 != 0
     }
+
+    /// Bits 2-3: `0x` for ERC20, `10` for external and `11` for internal
+    /// Vault balances.
+    pub fn sell_token_balance(&self) -> SellTokenSource {
+        match (self.as_u8() >> 2) & 0b11 {
+            0b10 => SellTokenSource::External,
+            0b11 => SellTokenSource::Internal,
+            _ => SellTokenSource::Erc20,
+        }
+    }
+
+    /// Bit 4: `0` for ERC20 and `1` for internal Vault balances.
+    pub fn buy_token_balance(&self) -> BuyTokenDestination {
+        if self.as_u8() & 0b1_0000 == 0 {
+            BuyTokenDestination::Erc20
+        } else {
+            BuyTokenDestination::Internal
+        }
+    }
+
+    /// Bits 5-6: `00` for EIP-712, `01` for eth_sign, `10` for EIP-1271 and
+    /// `11` for pre-sign.
+    pub fn signing_scheme(&self) -> SigningScheme {
+        match (self.as_u8() >> 5) & 0b11 {
+            0b00 => SigningScheme::Eip712,
+            0b01 => SigningScheme::EthSign,
+            0b10 => SigningScheme::Eip1271,
+            _ => SigningScheme::PreSign,
+        }
+    }
 }
 
 impl From<U256> for TradeFlags {
@@ -120,6 +216,13 @@ pub struct DecodedInteraction {
     pub call_data: Bytes<Vec<u8>>,
 }
 
+impl DecodedInteraction {
+    /// Whether the interaction sends native ETH to its target.
+    pub fn sends_value(&self) -> bool {
+        !self.value.is_zero()
+    }
+}
+
 impl From<(Address, U256, Bytes<Vec<u8>>)> for DecodedInteraction {
     fn from((target, value, call_data): (Address, U256, Bytes<Vec<u8>>)) -> Self {
         Self {
@@ -181,13 +284,14 @@ impl DecodedSettlement {
     /// id.
     pub const META_DATA_LEN: usize = 8;
 
+    /// Names of the settlement contract functions whose calldata starts with
+    /// the `(tokens, clearingPrices, trades, interactions)` arguments of
+    /// `settle` and can therefore be decoded into a `DecodedSettlement`.
+    pub const SETTLE_FUNCTIONS: &'static [&'static str] = &["settle"];
+
     pub fn new(input: &[u8]) -> Result<Self, DecodingError> {
-        let function = GPv2Settlement::raw_contract()
-            .abi
-            .function("settle")
-            .unwrap();
-        let without_selector = input
-            .strip_prefix(&function.selector())
+        let abi = &GPv2Settlement::raw_contract().abi;
+        let (function, without_selector) = settle_function(abi, Self::SETTLE_FUNCTIONS, input)
             .ok_or(DecodingError::InvalidSelector)?;
 
         // Decoding calldata without expecting metadata can succeed even if metadata
@@ -195,15 +299,21 @@ impl DecodedSettlement {
         if let Ok(decoded) = Self::try_new(without_selector, function, true) {
             return Ok(decoded);
         }
-        Self::try_new(without_selector, function, false).map_err(Into::into)
+        Self::try_new(without_selector, function, false)
     }
 
-    fn try_new(data: &[u8], function: &Function, with_metadata: bool) -> Result<Self> {
+    fn try_new(
+        data: &[u8],
+        function: &Function,
+        with_metadata: bool,
+    ) -> Result<Self, DecodingError> {
         let metadata_len = if with_metadata {
-            anyhow::ensure!(
-                data.len() % 32 == Self::META_DATA_LEN,
-                "calldata does not contain the expected number of bytes to include metadata"
-            );
+            if data.len() % 32 != Self::META_DATA_LEN {
+                return Err(anyhow::anyhow!(
+                    "calldata does not contain the expected number of bytes to include metadata"
+                )
+                .into());
+            }
             Self::META_DATA_LEN
         } else {
             0
@@ -217,7 +327,13 @@ impl DecodedSettlement {
             .context("decoding tokenized settlement calldata failed")?;
 
         let (tokens, clearing_prices, trades, interactions) = decoded;
-        Ok(Self {
+        if tokens.len() != clearing_prices.len() {
+            return Err(DecodingError::ClearingPricesMismatch {
+                tokens: tokens.len(),
+                clearing_prices: clearing_prices.len(),
+            });
+        }
+        let settlement = Self {
             tokens,
             clearing_prices,
             trades: trades
@@ -238,14 +354,183 @@ impl DecodedSettlement {
                 .collect(),
             interactions: interactions.map(|inner| inner.into_iter().map(Into::into).collect()),
             metadata: metadata.try_into().ok().map(Bytes),
-        })
+        };
+
+        // The calldata comes from arbitrary on-chain transactions so we can't
+        // rely on the token indices of the trades being sensible.
+        for trade in &settlement.trades {
+            for index in [trade.sell_token_index, trade.buy_token_index] {
+                if !token_index(index).is_some_and(|index| index < settlement.tokens.len()) {
+                    return Err(anyhow::anyhow!("trade token index {index} out of bounds").into());
+                }
+            }
+        }
+
+        Ok(settlement)
+    }
+
+    /// Encodes the settlement into `settle()` calldata with the metadata
+    /// appended.
+    pub fn encode(&self) -> Vec<u8> {
+        let settlement: DecodedSettlementTokenized = (
+            self.tokens.clone(),
+            self.clearing_prices.clone(),
+            self.trades
+                .iter()
+                .map(|trade| {
+                    (
+                        trade.sell_token_index,
+                        trade.buy_token_index,
+                        trade.receiver,
+                        trade.sell_amount,
+                        trade.buy_amount,
+                        trade.valid_to,
+                        Bytes(trade.app_data.0),
+                        trade.fee_amount,
+                        trade.flags.0,
+                        trade.executed_amount,
+                        trade.signature.clone(),
+                    )
+                })
+                .collect(),
+            self.interactions.each_ref().map(|interactions| {
+                interactions
+                    .iter()
+                    .map(|interaction| {
+                        (
+                            interaction.target,
+                            interaction.value,
+                            interaction.call_data.clone(),
+                        )
+                    })
+                    .collect()
+            }),
+        );
+        let mut calldata = encode_settlement(settlement);
+        if let Some(metadata) = &self.metadata {
+            calldata.extend_from_slice(&metadata.0);
+        }
+        calldata
+    }
+
+    pub fn stats(&self) -> SettlementStats {
+        SettlementStats {
+            trades: self.trades.len(),
+            interactions: self.interactions.iter().map(Vec::len).sum(),
+            calldata_len: self.encode().len(),
+        }
+    }
+
+    /// The executed amounts of every trade at the settlement's (adjusted)
+    /// clearing prices, computed like `GPv2Settlement.computeTradeExecution`
+    /// does. Returns `None` if the amounts of any trade can't be computed,
+    /// e.g. because of a zero price, in which case the settlement would revert.
+    pub fn executed_amounts(&self) -> Option<Vec<ExecutedAmounts>> {
+        self.trades
+            .iter()
+            .map(|trade| {
+                let sell_price = *self
+                    .clearing_prices
+                    .get(token_index(trade.sell_token_index)?)?;
+                let buy_price = *self
+                    .clearing_prices
+                    .get(token_index(trade.buy_token_index)?)?;
+
+                // The contract ignores `executed_amount` for fill-or-kill orders.
+                let partially_fillable = trade.flags.partially_fillable();
+                match trade.flags.order_kind() {
+                    OrderKind::Sell => {
+                        let sell = if partially_fillable {
+                            trade.executed_amount
+                        } else {
+                            trade.sell_amount
+                        };
+                        let buy = sell.checked_mul(sell_price)?.checked_ceil_div(&buy_price)?;
+                        Some(ExecutedAmounts { sell, buy })
+                    }
+                    OrderKind::Buy => {
+                        let buy = if partially_fillable {
+                            trade.executed_amount
+                        } else {
+                            trade.buy_amount
+                        };
+                        let sell = buy.checked_mul(buy_price)?.checked_div(sell_price)?;
+                        Some(ExecutedAmounts { sell, buy })
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The id of the auction this settlement claims to belong to. It is encoded
+    /// as a big-endian integer in the metadata.
+    pub fn auction_id(&self) -> Option<u64> {
+        self.metadata
+            .as_ref()
+            .map(|bytes| u64::from_be_bytes(bytes.0))
+    }
+
+    /// Interactions executed before any trade.
+    pub fn pre_interactions(&self) -> &[DecodedInteraction] {
+        &self.interactions[0]
+    }
+
+    /// Interactions executed after the sell amounts were transferred into the
+    /// settlement contract and before the buy amounts get transferred out.
+    pub fn intra_interactions(&self) -> &[DecodedInteraction] {
+        &self.interactions[1]
+    }
+
+    /// Interactions executed after all trades.
+    pub fn post_interactions(&self) -> &[DecodedInteraction] {
+        &self.interactions[2]
+    }
+
+    /// Returns the total amount of native ETH sent by the interactions of all
+    /// stages.
+    pub fn total_interaction_value(&self) -> U256 {
+        self.interactions
+            .iter()
+            .flatten()
+            .fold(U256::zero(), |acc, interaction| {
+                acc.saturating_add(interaction.value)
+            })
     }
 
     /// Returns the total surplus denominated in the native asset for the
     /// solution.
     pub fn total_surplus(&self, external_prices: &ExternalPrices) -> U256 {
+        self.sum_surplus(external_prices, Some)
+    }
+
+    /// Returns the total surplus denominated in the `reference` token for the
+    /// solution. Fails if the reference token has no external price.
+    pub fn total_surplus_in(
+        &self,
+        external_prices: &ExternalPrices,
+        reference: H160,
+    ) -> Result<U256> {
+        external_prices
+            .price(&reference)
+            .filter(|price| !price.is_zero())
+            .with_context(|| format!("missing external price for {reference:?}"))?;
+        Ok(self.sum_surplus(external_prices, |surplus| {
+            external_prices.try_get_token_amount(&surplus, reference)
+        }))
+    }
+
+    /// Sums the surplus of all trades after converting each trade's native
+    /// surplus with `convert`.
+    fn sum_surplus(
+        &self,
+        external_prices: &ExternalPrices,
+        convert: impl Fn(BigRational) -> Option<BigRational>,
+    ) -> U256 {
         self.trades.iter().fold(0.into(), |acc, trade| {
-            acc + match surplus(trade, &self.tokens, &self.clearing_prices, external_prices) {
+            let surplus = surplus(trade, &self.tokens, &self.clearing_prices, external_prices)
+                .and_then(&convert)
+                .and_then(|surplus| big_rational_to_u256(&surplus).ok());
+            acc + match surplus {
                 Some(surplus) => surplus,
                 None => {
                     tracing::warn!("possible incomplete surplus calculation");
@@ -349,8 +634,8 @@ impl DecodedSettlement {
                 let uniform_buy_price = self.clearing_prices.get(buy_index).cloned()?;
 
                 // get executed(adjusted) prices
-                let sell_index = trade.sell_token_index.as_u64() as usize;
-                let buy_index = trade.buy_token_index.as_u64() as usize;
+                let sell_index = token_index(trade.sell_token_index)?;
+                let buy_index = token_index(trade.buy_token_index)?;
                 let adjusted_sell_price = self.clearing_prices.get(sell_index).cloned()?;
                 let adjusted_buy_price = self.clearing_prices.get(buy_index).cloned()?;
 
@@ -390,10 +675,16 @@ impl DecodedSettlement {
             .try_get_native_amount(order.sell_token, u256_to_big_rational(&solver_fee))?;
         tracing::trace!(?fee, "fee after conversion to native token");
 
+        // Orders don't carry a fee policy yet so the protocol fee can't be
+        // determined and the whole fee gets attributed to the network.
+        tracing::debug!(order = %order.order_uid, "unknown protocol fee, using network fee only");
+
         Some(Fees {
             order: order.order_uid,
             sell: solver_fee,
             native: big_rational_to_u256(&fee).ok()?,
+            protocol: U256::zero(),
+            network: solver_fee,
         })
     }
 }
@@ -407,16 +698,28 @@ pub struct Fees {
     pub sell: U256,
     /// The executed fees in the native token.
     pub native: U256,
+    /// The part of the sell token fees that the protocol takes.
+    pub protocol: U256,
+    /// The part of the sell token fees that reimburses the gas costs of the
+    /// settlement.
+    pub network: U256,
+}
+
+/// Converts a token index of a trade into a `usize` without wrapping or
+/// panicking for values that don't fit.
+fn token_index(index: U256) -> Option<usize> {
+    (index <= U256::from(usize::MAX)).then(|| index.as_usize())
 }
 
+/// Returns the surplus of the trade denominated in the native asset.
 fn surplus(
     trade: &DecodedTrade,
     tokens: &[Address],
     clearing_prices: &[U256],
     external_prices: &ExternalPrices,
-) -> Option<U256> {
-    let sell_token_index = trade.sell_token_index.as_u64() as usize;
-    let buy_token_index = trade.buy_token_index.as_u64() as usize;
+) -> Option<BigRational> {
+    let sell_token_index = token_index(trade.sell_token_index)?;
+    let buy_token_index = token_index(trade.buy_token_index)?;
 
     let sell_token_clearing_price = clearing_prices.get(sell_token_index)?.to_big_rational();
     let buy_token_clearing_price = clearing_prices.get(buy_token_index)?.to_big_rational();
@@ -440,19 +743,16 @@ fn surplus(
         &buy_token_clearing_price,
     )?;
 
-    let normalized_surplus = match kind {
+    match kind {
         OrderKind::Sell => {
             let buy_token = tokens.get(buy_token_index)?;
-            external_prices.try_get_native_amount(*buy_token, surplus / buy_token_clearing_price)?
+            external_prices.try_get_native_amount(*buy_token, surplus / buy_token_clearing_price)
         }
         OrderKind::Buy => {
             let sell_token = tokens.get(sell_token_index)?;
-            external_prices
-                .try_get_native_amount(*sell_token, surplus / sell_token_clearing_price)?
+            external_prices.try_get_native_amount(*sell_token, surplus / sell_token_clearing_price)
         }
-    };
-
-    big_rational_to_u256(&normalized_surplus).ok()
+    }
 }
 
 fn trade_surplus(
@@ -528,9 +828,37 @@ fn sell_order_surplus(
     }
 }
 
+/// Finds the function of `names` whose selector `input` starts with and
+/// returns it together with the remaining input. Names that are not part of
+/// the ABI get logged and skipped.
+fn settle_function<'a, 'b>(
+    abi: &'a Contract,
+    names: &[&str],
+    input: &'b [u8],
+) -> Option<(&'a Function, &'b [u8])> {
+    names
+        .iter()
+        .filter_map(|name| match abi.function(name) {
+            Ok(function) => Some(function),
+            Err(err) => {
+                tracing::warn!(?err, name, "settle function missing from the ABI");
+                None
+            }
+        })
+        .find_map(|function| {
+            let data = input.strip_prefix(&function.selector())?;
+            Some((function, data))
+        })
+}
+
 #[derive(Debug)]
 pub enum DecodingError {
     InvalidSelector,
+    /// The number of clearing prices does not match the number of tokens.
+    ClearingPricesMismatch {
+        tokens: usize,
+        clearing_prices: usize,
+    },
     Other(anyhow::Error),
 }
 
@@ -544,6 +872,12 @@ impl From<DecodingError> for anyhow::Error {
     fn from(err: DecodingError) -> Self {
         match err {
             DecodingError::InvalidSelector => anyhow::anyhow!("invalid function selector"),
+            DecodingError::ClearingPricesMismatch {
+                tokens,
+                clearing_prices,
+            } => anyhow::anyhow!(
+                "settlement has {clearing_prices} clearing prices for {tokens} tokens"
+            ),
             DecodingError::Other(err) => err,
         }
     }
@@ -650,6 +984,24 @@ mod tests {
         let surplus = settlement.total_surplus(&external_prices).to_f64_lossy(); // to_f64_lossy() to mimic what happens when value is saved for solver
                                                                                  // competition
         assert_eq!(surplus, 33350701806766732.);
+
+        let surplus_in_native = settlement
+            .total_surplus_in(&external_prices, native_token)
+            .unwrap();
+        assert_eq!(
+            surplus_in_native,
+            settlement.total_surplus(&external_prices)
+        );
+        let surplus_in_usdc = settlement
+            .total_surplus_in(
+                &external_prices,
+                addr!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"),
+            )
+            .unwrap();
+        assert!(surplus_in_usdc.as_u64().abs_diff(55_897_951) <= 1);
+        assert!(settlement
+            .total_surplus_in(&external_prices, H160([0x42; 20]))
+            .is_err());
     }
 
     #[test]
@@ -758,20 +1110,9 @@ mod tests {
         assert_eq!(fees, 45377573614605000.);
     }
 
-    #[test]
-    fn total_fees_test_partial_limit_order() {
-        // transaction hash:
-        // 0x00e0e45ccc01b1bc99350444742cf5b4701d0c3eb85bc8c8f60a07e1e8cc4a36
-
-        // From solver competition table:
-
-        // external prices (auction values):
-        // 0xba386a4ca26b85fd057ab1ef86e3dc7bdeb5ce70: 8302940
-        // 0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2: 1000000000000000000
-
-        // fees: 3768095572151423
-
-        let call_data = hex_literal::hex!(
+    /// Call data of a settlement with a single partially fillable limit order.
+    fn call_data_with_partial_limit_order() -> Vec<u8> {
+        hex_literal::hex!(
             "13d79a0b0000000000000000000000000000000000000000000000000000000000000080000000000000000000000000000000000000000000000000000000000000012000000000000000000000000000000000000000000000000000000000000001c000000000000000000000000000000000000000000000000000000000000003e
             00000000000000000000000000000000000000000000000000000000000000004000000000000000000000000ba386a4ca26b85fd057ab1ef86e3dc7bdeb5ce70000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2000000000000000000000000ba386a4ca26b85fd057ab1ef86e3dc7bdeb5ce700000000
             00000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000083732b0000000000000000000000000000000000000000000000000de0b6b3a7640000000000000000000
@@ -785,7 +1126,44 @@ mod tests {
             e0000000000000000000000000000000000000000000000000ff962d452d79e2a0000000000000000000000000000000000000001b02aeadbd4ac223168f3b31200000000000000000000000000000000000000000000000000000000000000a00000000000000000000000009008d19f58aabd9ed0d60971565aa8510560ab41fffffff
             fffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000ba386a4ca26b85fd057ab1ef86e3dc7bdeb5ce70000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2000000000000000
             000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        )
+        .to_vec()
+    }
+
+    #[test]
+    fn executed_amounts_of_partial_limit_order() {
+        let settlement = DecodedSettlement::new(&call_data_with_partial_limit_order()).unwrap();
+        assert!(settlement.trades[0].flags.partially_fillable());
+        assert_eq!(
+            settlement.executed_amounts(),
+            Some(vec![ExecutedAmounts {
+                sell: 134069619089011499167823218927u128.into(),
+                buy: 1151059833376998393u128.into(),
+            }])
         );
+
+        // Amounts can't be computed with a zero clearing price.
+        let settlement = DecodedSettlement {
+            clearing_prices: vec![U256::zero(); 4],
+            ..settlement
+        };
+        assert_eq!(settlement.executed_amounts(), None);
+    }
+
+    #[test]
+    fn total_fees_test_partial_limit_order() {
+        // transaction hash:
+        // 0x00e0e45ccc01b1bc99350444742cf5b4701d0c3eb85bc8c8f60a07e1e8cc4a36
+
+        // From solver competition table:
+
+        // external prices (auction values):
+        // 0xba386a4ca26b85fd057ab1ef86e3dc7bdeb5ce70: 8302940
+        // 0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2: 1000000000000000000
+
+        // fees: 3768095572151423
+
+        let call_data = call_data_with_partial_limit_order();
         let settlement = DecodedSettlement::new(&call_data).unwrap();
 
         //calculate fees
@@ -816,6 +1194,11 @@ mod tests {
                 solver_determines_fee: true,
             },
         ];
+        let executions = settlement.order_executions(&external_prices, orders.clone());
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].protocol, U256::zero());
+        assert_eq!(executions[0].network, executions[0].sell);
+
         let fees = settlement
             .total_fees(&external_prices, orders)
             .to_f64_lossy(); // to_f64_lossy() to mimic what happens when value is saved for solver
@@ -936,9 +1319,9 @@ mod tests {
         assert_eq!(fees, 13630555109200196.);
     }
 
-    #[test]
-    fn decodes_metadata() {
-        let call_data = hex_literal::hex!(
+    /// Call data of a settlement which unwraps WETH in an intra-interaction.
+    fn call_data_with_weth_unwrap() -> Vec<u8> {
+        hex_literal::hex!(
             "13d79a0b
              0000000000000000000000000000000000000000000000000000000000000080
              00000000000000000000000000000000000000000000000000000000000000e0
@@ -1002,14 +1385,19 @@ mod tests {
              d49c29bf00000000000000000000000000000000000000000000000000000000
              0000000000000000000000000000000000000000000000000000000000000000"
         )
-        .to_vec();
+        .to_vec()
+    }
+
+    #[test]
+    fn decodes_metadata() {
+        let call_data = call_data_with_weth_unwrap();
 
         let original = DecodedSettlement::new(&call_data).unwrap();
 
         // If not enough call data got appended we parse it like it didn't have any
         // Not enough metadata appended to the calldata.
         let metadata = [42; DecodedSettlement::META_DATA_LEN - 1];
-        let with_metadata = [call_data.clone(), metadata.to_vec()].concat();
+        let with_metadata = [call_data, metadata.to_vec()].concat();
         assert_eq!(original, DecodedSettlement::new(&with_metadata).unwrap());
 
         // Same if too much metadata gets added.
@@ -1022,12 +1410,245 @@ mod tests {
         let with_metadata = [call_data, metadata.to_vec()].concat();
         let with_metadata = DecodedSettlement::new(&with_metadata).unwrap();
         assert_eq!(with_metadata.metadata, Some(Bytes(metadata)));
+        assert_eq!(with_metadata.auction_id(), Some(0x2a2a_2a2a_2a2a_2a2a));
 
         // Content of the remaining fields is identical to the original
         let metadata_removed_again = DecodedSettlement {
             metadata: None,
             ..with_metadata
         };
+        assert_eq!(metadata_removed_again.auction_id(), None);
         assert_eq!(original, metadata_removed_again);
     }
+
+    #[test]
+    fn rejects_unknown_selectors() {
+        let call_data = call_data_with_weth_unwrap();
+        let abi = &GPv2Settlement::raw_contract().abi;
+        let settle = abi.function("settle").unwrap().selector();
+        let swap = abi.function("swap").unwrap().selector();
+
+        let with_selector =
+            |selector: [u8; 4]| [selector.as_slice(), &call_data[settle.len()..]].concat();
+        assert!(DecodedSettlement::new(&with_selector(settle)).is_ok());
+        assert!(matches!(
+            DecodedSettlement::new(&with_selector(swap)),
+            Err(DecodingError::InvalidSelector)
+        ));
+        assert!(matches!(
+            DecodedSettlement::new(&[]),
+            Err(DecodingError::InvalidSelector)
+        ));
+    }
+
+    #[test]
+    fn skips_settle_functions_missing_from_abi() {
+        let call_data = call_data_with_weth_unwrap();
+        let abi = &GPv2Settlement::raw_contract().abi;
+
+        let (function, data) =
+            settle_function(abi, &["not a function", "settle"], &call_data).unwrap();
+        assert_eq!(function.name, "settle");
+        assert_eq!(data, &call_data[4..]);
+
+        assert!(settle_function(abi, &["not a function"], &call_data).is_none());
+    }
+
+    #[test]
+    fn settlement_stats() {
+        let call_data = call_data_with_weth_unwrap();
+        let settlement = DecodedSettlement::new(&call_data).unwrap();
+        assert_eq!(settlement.encode(), call_data);
+        assert_eq!(
+            settlement.stats(),
+            SettlementStats {
+                trades: 1,
+                // a Balancer swap and the WETH unwrap
+                interactions: 2,
+                calldata_len: 1956,
+            }
+        );
+
+        let metadata = [42; DecodedSettlement::META_DATA_LEN];
+        let with_metadata = [call_data, metadata.to_vec()].concat();
+        let settlement = DecodedSettlement::new(&with_metadata).unwrap();
+        assert_eq!(settlement.encode(), with_metadata);
+        assert_eq!(settlement.stats().calldata_len, 1964);
+    }
+
+    #[test]
+    fn interaction_values() {
+        let mut settlement = DecodedSettlement::new(&call_data_with_weth_unwrap()).unwrap();
+
+        assert!(settlement.pre_interactions().is_empty());
+        assert!(settlement.post_interactions().is_empty());
+        let unwrap = settlement.intra_interactions().last().unwrap();
+        assert_eq!(
+            unwrap.target,
+            addr!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+        );
+        assert_eq!(unwrap.call_data.0[..4], hex_literal::hex!("2e1a7d4d"));
+        assert!(!unwrap.sends_value());
+        assert_eq!(settlement.total_interaction_value(), U256::zero());
+
+        settlement.interactions[0].push(DecodedInteraction {
+            target: H160([1; 20]),
+            value: 1_000.into(),
+            call_data: Default::default(),
+        });
+        settlement.interactions[2].push(DecodedInteraction {
+            target: H160([2; 20]),
+            value: 337.into(),
+            call_data: Default::default(),
+        });
+        assert!(settlement.interactions[0].last().unwrap().sends_value());
+        assert_eq!(settlement.total_interaction_value(), 1_337.into());
+    }
+
+    #[test]
+    fn decodes_trade_flags() {
+        let flags = |bits: u8| TradeFlags(bits.into());
+
+        assert_eq!(flags(0b0000).sell_token_balance(), SellTokenSource::Erc20);
+        assert_eq!(flags(0b0100).sell_token_balance(), SellTokenSource::Erc20);
+        assert_eq!(
+            flags(0b1000).sell_token_balance(),
+            SellTokenSource::External
+        );
+        assert_eq!(
+            flags(0b1100).sell_token_balance(),
+            SellTokenSource::Internal
+        );
+
+        assert_eq!(
+            flags(0b0_0000).buy_token_balance(),
+            BuyTokenDestination::Erc20
+        );
+        assert_eq!(
+            flags(0b1_0000).buy_token_balance(),
+            BuyTokenDestination::Internal
+        );
+
+        assert_eq!(flags(0b000_0000).signing_scheme(), SigningScheme::Eip712);
+        assert_eq!(flags(0b010_0000).signing_scheme(), SigningScheme::EthSign);
+        assert_eq!(flags(0b100_0000).signing_scheme(), SigningScheme::Eip1271);
+        assert_eq!(flags(0b110_0000).signing_scheme(), SigningScheme::PreSign);
+
+        // All fields are decoded independently of each other.
+        let flags = flags(0b101_1011);
+        assert_eq!(flags.order_kind(), OrderKind::Buy);
+        assert!(flags.partially_fillable());
+        assert_eq!(flags.sell_token_balance(), SellTokenSource::External);
+        assert_eq!(flags.buy_token_balance(), BuyTokenDestination::Internal);
+        assert_eq!(flags.signing_scheme(), SigningScheme::Eip1271);
+    }
+
+    fn settlement_with_single_trade(
+        clearing_prices: Vec<U256>,
+        sell_token_index: U256,
+        buy_token_index: U256,
+    ) -> Vec<u8> {
+        encode_settlement((
+            vec![H160([1; 20]), H160([2; 20])],
+            clearing_prices,
+            vec![(
+                sell_token_index,
+                buy_token_index,
+                H160([3; 20]),
+                1.into(),
+                1.into(),
+                0,
+                Bytes([0; 32]),
+                0.into(),
+                0.into(),
+                1.into(),
+                Bytes(vec![]),
+            )],
+            Default::default(),
+        ))
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_token_indices() {
+        let call_data = |sell_token_index, buy_token_index| {
+            settlement_with_single_trade(
+                vec![1.into(), 1.into()],
+                sell_token_index,
+                buy_token_index,
+            )
+        };
+
+        let settlement = DecodedSettlement::new(&call_data(0.into(), 1.into())).unwrap();
+        assert_eq!(settlement.trades[0].sell_token_index, 0.into());
+        assert_eq!(settlement.trades[0].buy_token_index, 1.into());
+
+        let invalid = [
+            2.into(),
+            u32::MAX.into(),
+            U256::from(u64::MAX),
+            U256::from(u64::MAX) + 1,
+            U256::one() << 128,
+            U256::MAX,
+        ];
+        for index in invalid {
+            for (sell_token_index, buy_token_index) in [(index, 0.into()), (0.into(), index)] {
+                assert!(matches!(
+                    DecodedSettlement::new(&call_data(sell_token_index, buy_token_index)),
+                    Err(DecodingError::Other(_))
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_clearing_prices_mismatch() {
+        let call_data = settlement_with_single_trade(vec![1.into()], 0.into(), 0.into());
+        assert!(matches!(
+            DecodedSettlement::new(&call_data),
+            Err(DecodingError::ClearingPricesMismatch {
+                tokens: 2,
+                clearing_prices: 1,
+            })
+        ));
+    }
+
+    #[test]
+    fn trade_flags_round_trip() {
+        let kinds = [OrderKind::Sell, OrderKind::Buy];
+        let sell_token_balances = [
+            SellTokenSource::Erc20,
+            SellTokenSource::External,
+            SellTokenSource::Internal,
+        ];
+        let buy_token_balances = [BuyTokenDestination::Erc20, BuyTokenDestination::Internal];
+        let signing_schemes = [
+            SigningScheme::Eip712,
+            SigningScheme::EthSign,
+            SigningScheme::Eip1271,
+            SigningScheme::PreSign,
+        ];
+
+        for combination in itertools::iproduct!(
+            kinds,
+            [false, true],
+            sell_token_balances,
+            buy_token_balances,
+            signing_schemes
+        ) {
+            let (kind, partially_fillable, sell_token_balance, buy_token_balance, signing_scheme) =
+                combination;
+            let flags = TradeFlags::from(TradeFlags::encode(
+                kind,
+                partially_fillable,
+                sell_token_balance,
+                buy_token_balance,
+                signing_scheme,
+            ));
+            assert_eq!(flags.order_kind(), kind);
+            assert_eq!(flags.partially_fillable(), partially_fillable);
+            assert_eq!(flags.sell_token_balance(), sell_token_balance);
+            assert_eq!(flags.buy_token_balance(), buy_token_balance);
+            assert_eq!(flags.signing_scheme(), signing_scheme);
+        }
+    }
 }
@@ -148,6 +148,15 @@ pub mod solve {
         pub score: U256,
         /// Address used by the driver to submit the settlement onchain.
         pub submission_address: H160,
+        /// Name of the solver which produced the solution.
+        #[serde(default)]
+        pub solver: Option<String>,
+        /// Estimated gas used by the solution.
+        #[serde(default)]
+        pub gas: Option<u64>,
+        /// Surplus of the solution denominated in wei of the native token.
+        #[serde(default)]
+        pub surplus: Option<f64>,
     }
 
     #[derive(Clone, Debug, Default, Deserialize)]
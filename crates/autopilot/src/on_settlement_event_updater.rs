@@ -36,7 +36,7 @@ use {
             on_settlement_event_updater::{AuctionData, AuctionId, SettlementUpdate},
             Postgres,
         },
-        decoded_settlement::{DecodedSettlement, DecodingError},
+        decoded_settlement::{DecodedSettlement, DecodingError, OrderExecution},
     },
     anyhow::{anyhow, Context, Result},
     contracts::GPv2Settlement,
@@ -46,7 +46,7 @@ use {
         Web3,
     },
     futures::StreamExt,
-    primitive_types::{H160, H256},
+    primitive_types::{H160, H256, U256},
     shared::{event_handling::MAX_REORG_BLOCK_COUNT, external_prices::ExternalPrices},
     sqlx::PgConnection,
     web3::types::{Transaction, TransactionId},
@@ -97,7 +97,12 @@ impl OnSettlementEventUpdater {
             .try_into()
             .context("convert block")?;
 
-        let mut ex = self.db.0.begin().await.context("acquire DB connection")?;
+        let mut ex = self
+            .db
+            .pool
+            .begin()
+            .await
+            .context("acquire DB connection")?;
         let event = match database::auction_transaction::get_settlement_event_without_tx_info(
             &mut ex,
             reorg_safe_block,
@@ -191,34 +196,14 @@ impl OnSettlementEventUpdater {
                 "observations input"
             );
 
-            // surplus and fees calculation
-            match DecodedSettlement::new(&transaction.input.0) {
-                Ok(settlement) => {
-                    let surplus = settlement.total_surplus(&external_prices);
-                    let fee = settlement.total_fees(&external_prices, orders.clone());
-                    let order_executions = settlement.order_executions(&external_prices, orders);
-
-                    update.auction_data = Some(AuctionData {
-                        auction_id,
-                        surplus,
-                        fee,
-                        gas_used,
-                        effective_gas_price,
-                        order_executions: order_executions
-                            .iter()
-                            .map(|fees| (fees.order, fees.sell))
-                            .collect(),
-                    });
-                }
-                Err(DecodingError::InvalidSelector) => {
-                    // we indexed a transaction initiated by solver, that was not a settlement
-                    // for this case we want to have the entry in observations table but with zeros
-                    update.auction_data = Some(Default::default());
-                }
-                Err(err) => {
-                    return Err(err.into());
-                }
-            }
+            update.auction_data = Some(auction_data(
+                &transaction.input.0,
+                auction_id,
+                gas_used,
+                effective_gas_price,
+                &external_prices,
+                orders,
+            )?);
         }
 
         tracing::debug!(?hash, ?update, "updating settlement details for tx");
@@ -240,8 +225,8 @@ impl OnSettlementEventUpdater {
         tx: &Transaction,
     ) -> Result<Option<i64>> {
         let tx_from = tx.from.context("tx is missing sender")?;
-        let metadata = match DecodedSettlement::new(&tx.input.0) {
-            Ok(settlement) => settlement.metadata,
+        let auction_id = match DecodedSettlement::new(&tx.input.0) {
+            Ok(settlement) => settlement.auction_id(),
             Err(err) => {
                 tracing::warn!(
                     ?tx,
@@ -251,8 +236,8 @@ impl OnSettlementEventUpdater {
                 return Ok(None);
             }
         };
-        let auction_id = match metadata {
-            Some(bytes) => i64::from_be_bytes(bytes.0),
+        let auction_id = match auction_id.and_then(|id| i64::try_from(id).ok()) {
+            Some(auction_id) => auction_id,
             None => {
                 tracing::warn!(?tx, "could not recover the auction_id from the calldata");
                 return Ok(None);
@@ -291,6 +276,53 @@ impl OnSettlementEventUpdater {
     }
 }
 
+/// Computes the surplus and fees of a settlement transaction.
+fn auction_data(
+    calldata: &[u8],
+    auction_id: AuctionId,
+    gas_used: U256,
+    effective_gas_price: U256,
+    external_prices: &ExternalPrices,
+    orders: Vec<OrderExecution>,
+) -> Result<AuctionData> {
+    match DecodedSettlement::new(calldata) {
+        Ok(settlement) => {
+            let surplus = settlement.total_surplus(external_prices);
+            let fee = settlement.total_fees(external_prices, orders.clone());
+            let order_executions = settlement.order_executions(external_prices, orders);
+
+            Ok(AuctionData {
+                auction_id,
+                surplus,
+                fee,
+                gas_used,
+                effective_gas_price,
+                order_executions: order_executions
+                    .iter()
+                    .map(|fees| (fees.order, fees.sell))
+                    .collect(),
+            })
+        }
+        Err(DecodingError::InvalidSelector) => {
+            // we indexed a transaction initiated by solver, that was not a settlement
+            // for this case we want to have the entry in observations table but with zeros
+            Ok(Default::default())
+        }
+        Err(err @ DecodingError::ClearingPricesMismatch { .. }) => {
+            // The contract accepts such settlements so they are valid on chain. Returning
+            // an error would make us retry the same event forever.
+            tracing::warn!(?err, "unable to compute surplus and fees of settlement");
+            Ok(AuctionData {
+                auction_id,
+                gas_used,
+                effective_gas_price,
+                ..Default::default()
+            })
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -299,13 +331,71 @@ mod tests {
         sqlx::Executor,
     };
 
+    #[test]
+    fn no_surplus_and_fees_for_clearing_prices_mismatch() {
+        // `settle()` with 2 tokens but only 1 clearing price and no trades or
+        // interactions.
+        let calldata = hex_literal::hex!(
+            "13d79a0b
+             0000000000000000000000000000000000000000000000000000000000000080
+             00000000000000000000000000000000000000000000000000000000000000e0
+             0000000000000000000000000000000000000000000000000000000000000120
+             0000000000000000000000000000000000000000000000000000000000000140
+             0000000000000000000000000000000000000000000000000000000000000002
+             0000000000000000000000000101010101010101010101010101010101010101
+             0000000000000000000000000202020202020202020202020202020202020202
+             0000000000000000000000000000000000000000000000000000000000000001
+             0000000000000000000000000000000000000000000000000000000000000001
+             0000000000000000000000000000000000000000000000000000000000000000
+             0000000000000000000000000000000000000000000000000000000000000060
+             0000000000000000000000000000000000000000000000000000000000000080
+             00000000000000000000000000000000000000000000000000000000000000a0
+             0000000000000000000000000000000000000000000000000000000000000000
+             0000000000000000000000000000000000000000000000000000000000000000
+             0000000000000000000000000000000000000000000000000000000000000000"
+        );
+        assert!(matches!(
+            DecodedSettlement::new(&calldata),
+            Err(DecodingError::ClearingPricesMismatch { .. })
+        ));
+
+        let data = auction_data(
+            &calldata,
+            AuctionId::Colocated(1),
+            100.into(),
+            200.into(),
+            &Default::default(),
+            Vec::new(),
+        )
+        .unwrap();
+        assert!(matches!(data.auction_id, AuctionId::Colocated(1)));
+        assert_eq!(data.gas_used, 100.into());
+        assert_eq!(data.effective_gas_price, 200.into());
+        assert_eq!(data.surplus, U256::zero());
+        assert_eq!(data.fee, U256::zero());
+        assert!(data.order_executions.is_empty());
+
+        // Calldata that can't be decoded at all is still an error.
+        assert!(auction_data(
+            &calldata[..100],
+            AuctionId::Colocated(1),
+            100.into(),
+            200.into(),
+            &Default::default(),
+            Vec::new(),
+        )
+        .is_err());
+    }
+
     #[tokio::test]
     #[ignore]
     async fn manual_node_test() {
         // TODO update test
         observe::tracing::initialize_reentrant("autopilot=trace");
-        let db = Postgres::new("postgresql://").await.unwrap();
-        database::clear_DANGER(&db.0).await.unwrap();
+        let db = Postgres::new("postgresql://", Default::default())
+            .await
+            .unwrap();
+        database::clear_DANGER(&db.pool).await.unwrap();
         let transport = shared::ethrpc::create_env_test_transport();
         let web3 = Web3::new(transport);
 
@@ -324,13 +414,13 @@ mod tests {
 INSERT INTO settlements (block_number, log_index, solver, tx_hash, tx_from, tx_nonce)
 VALUES (15875801, 405, '\x', '\x0e9d0f4ea243ac0f02e1d3ecab3fea78108d83bfca632b30e9bc4acb22289c5a', NULL, NULL)
     ;";
-        updater.db.0.execute(query).await.unwrap();
+        updater.db.pool.execute(query).await.unwrap();
 
         let query = r"
 INSERT INTO auction_transaction (auction_id, tx_from, tx_nonce)
 VALUES (0, '\xa21740833858985e4d801533a808786d3647fb83', 4701)
     ;";
-        updater.db.0.execute(query).await.unwrap();
+        updater.db.pool.execute(query).await.unwrap();
 
         let query = r"
 INSERT INTO auction_prices (auction_id, token, price)
@@ -339,7 +429,7 @@ VALUES (0, '\x056fd409e1d7a124bd7017459dfea2f387b6d5cd', 63477957279334750883433
         (0, '\xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48', 634553336916241343152390144)
             ;";
 
-        updater.db.0.execute(query).await.unwrap();
+        updater.db.pool.execute(query).await.unwrap();
 
         assert!(updater.update(15875900).await.unwrap());
 
@@ -349,7 +439,7 @@ FROM settlements
 WHERE block_number = 15875801 AND log_index = 405
         ;";
         let (tx_from, tx_nonce): (Vec<u8>, i64) = sqlx::query_as(query)
-            .fetch_one(&updater.db.0)
+            .fetch_one(&updater.db.pool)
             .await
             .unwrap();
         assert_eq!(
@@ -363,7 +453,7 @@ SELECT auction_id, tx_from, tx_nonce
 FROM auction_transaction
         ;";
         let (auction_id, tx_from, tx_nonce): (i64, Vec<u8>, i64) = sqlx::query_as(query)
-            .fetch_one(&updater.db.0)
+            .fetch_one(&updater.db.pool)
             .await
             .unwrap();
         assert_eq!(auction_id, 0);
@@ -376,7 +466,7 @@ FROM auction_transaction
         // assert that the prices are updated
         let query = r#"SELECT * FROM auction_prices;"#;
         let prices: Vec<AuctionPrice> = sqlx::query_as(query)
-            .fetch_all(&updater.db.0)
+            .fetch_all(&updater.db.pool)
             .await
             .unwrap();
         assert_eq!(prices.len(), 2);
@@ -384,7 +474,7 @@ FROM auction_transaction
         // assert that the observations are updated
         let query = r#"SELECT * FROM settlement_observations;"#;
         let observation: Observation = sqlx::query_as(query)
-            .fetch_one(&updater.db.0)
+            .fetch_one(&updater.db.pool)
             .await
             .unwrap();
         assert_eq!(observation.gas_used, 179155.into());
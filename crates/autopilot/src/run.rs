@@ -96,11 +96,28 @@ pub async fn start(args: impl Iterator<Item = String>) {
 pub async fn run(args: Arguments) {
     assert!(args.shadow.is_none(), "cannot run in shadow mode");
 
-    let db = Postgres::new(args.db_url.as_str()).await.unwrap();
+    let db_config = crate::database::Config {
+        order_events_dedup_window: args.dedup_order_events.then(|| {
+            chrono::Duration::from_std(args.order_events_dedup_window)
+                .expect("order events dedup window out of range")
+        }),
+    };
+    let db = Postgres::new(args.db_url.as_str(), db_config)
+        .await
+        .unwrap();
     tokio::task::spawn(
         crate::database::database_metrics(db.clone())
             .instrument(tracing::info_span!("database_metrics")),
     );
+    if let Some(days) = args.order_events_retention_days {
+        tokio::task::spawn(
+            crate::database::order_events::order_events_cleanup(
+                db.clone(),
+                chrono::Duration::days(days.into()),
+            )
+            .instrument(tracing::info_span!("order_events_cleanup")),
+        );
+    }
 
     let http_factory = HttpClientFactory::new(&args.http_client);
     let web3 = shared::ethrpc::web3(
@@ -242,6 +259,11 @@ pub async fn run(args: Arguments) {
     allowed_tokens.push(model::order::BUY_ETH_ADDRESS);
     let unsupported_tokens = args.unsupported_tokens.clone();
 
+    // The token owner finders share this list. It gets updated at runtime if
+    // a base tokens file is configured.
+    let owner_finder_base_tokens = token_owner_finder::liquidity::BaseTokenList::new(
+        base_tokens.tokens().iter().copied().collect(),
+    );
     let finder = token_owner_finder::init(
         &args.token_owner_finder,
         web3.clone(),
@@ -250,7 +272,7 @@ pub async fn run(args: Arguments) {
         &pair_providers,
         vault.as_ref(),
         uniswapv3_factory.as_ref(),
-        &base_tokens,
+        &owner_finder_base_tokens,
     )
     .await
     .expect("failed to initialize token owner finders");
@@ -266,6 +288,7 @@ pub async fn run(args: Arguments) {
                 ),
                 finder,
                 settlement_contract: settlement_contract.address(),
+                transfer_rounds: args.token_quality_transfer_rounds,
             }),
             args.token_quality_cache_expiry,
         ))
@@ -289,6 +312,7 @@ pub async fn run(args: Arguments) {
         maximum_recent_block_age: args.shared.pool_cache_maximum_recent_block_age,
         max_retries: args.shared.pool_cache_maximum_retries,
         delay_between_retries: args.shared.pool_cache_delay_between_retries_seconds,
+        negative_ttl: None,
     };
     let pool_fetcher = Arc::new(
         PoolCache::new(
@@ -1,6 +1,6 @@
 use {
     crate::database::Postgres,
-    anyhow::Result,
+    anyhow::{anyhow, Result},
     bigdecimal::BigDecimal,
     database::order_events::OrderEventLabel,
     ethrpc::current_block::CurrentBlockStream,
@@ -17,7 +17,7 @@ use {
     prometheus::{IntCounter, IntCounterVec, IntGauge, IntGaugeVec},
     shared::{
         account_balances::{BalanceFetching, Query},
-        bad_token::BadTokenDetecting,
+        bad_token::{BadTokenDetecting, TokenQuality},
         price_estimation::native_price_cache::CachingNativePriceEstimator,
         remaining_amounts,
         signature_validator::{SignatureCheck, SignatureValidating},
@@ -543,9 +543,15 @@ async fn filter_unsupported_tokens(
     let mut index = 0;
     'outer: while index < orders.len() {
         for token in orders[index].data.token_pair().unwrap() {
-            if !bad_token.detect(token).await?.is_good() {
-                orders.swap_remove(index);
-                continue 'outer;
+            match bad_token.detect(token).await? {
+                TokenQuality::Good => (),
+                TokenQuality::Bad { .. } => {
+                    orders.swap_remove(index);
+                    continue 'outer;
+                }
+                TokenQuality::Unknown { reason } => {
+                    return Err(anyhow!("unknown quality of token {token:?}: {reason}"))
+                }
             }
         }
         index += 1;
@@ -690,7 +696,7 @@ mod tests {
         },
         primitive_types::H160,
         shared::{
-            bad_token::list_based::ListBasedDetector,
+            bad_token::{list_based::ListBasedDetector, MockBadTokenDetecting},
             price_estimation::{native::MockNativePriceEstimating, PriceEstimationError},
             signature_validator::{MockSignatureValidating, SignatureValidationError},
         },
@@ -988,6 +994,19 @@ mod tests {
         assert_eq!(result, &orders[1..2]);
     }
 
+    #[test]
+    fn filter_unsupported_tokens_fails_on_unknown_quality() {
+        let mut bad_token = MockBadTokenDetecting::new();
+        bad_token
+            .expect_detect()
+            .returning(|_| Ok(TokenQuality::unknown("node error")));
+        let orders = vec![OrderBuilder::default().build()];
+        let result = filter_unsupported_tokens(orders, &bad_token)
+            .now_or_never()
+            .unwrap();
+        assert!(result.is_err());
+    }
+
     #[test]
     fn filters_mispriced_orders() {
         let sell_token = H160([1; 20]);
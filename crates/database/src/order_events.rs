@@ -4,7 +4,7 @@
 use {
     crate::OrderUid,
     chrono::Utc,
-    sqlx::{types::chrono::DateTime, PgConnection},
+    sqlx::{types::chrono::DateTime, PgConnection, QueryBuilder},
 };
 
 /// Describes what kind of event was registered for an order.
@@ -66,3 +66,206 @@ VALUES ($1, $2, $3)
         .await
         .map(|_| ())
 }
+
+/// Inserts multiple rows into the `order_events` table with as few
+/// statements as possible.
+pub async fn insert_order_events(
+    ex: &mut PgConnection,
+    events: &[OrderEvent],
+) -> Result<(), sqlx::Error> {
+    // Postgres supports at most `u16::MAX` bind parameters per statement.
+    const MAX_EVENTS_PER_STATEMENT: usize = u16::MAX as usize / 3;
+
+    for chunk in events.chunks(MAX_EVENTS_PER_STATEMENT) {
+        let mut query =
+            QueryBuilder::new("INSERT INTO order_events (order_uid, timestamp, label) ");
+        query.push_values(chunk, |mut row, event| {
+            row.push_bind(event.order_uid)
+                .push_bind(event.timestamp)
+                .push_bind(event.label);
+        });
+        query.build().execute(&mut *ex).await?;
+    }
+    Ok(())
+}
+
+/// Fetches all events of an order from oldest to newest.
+pub async fn fetch_order_events(
+    ex: &mut PgConnection,
+    order: &OrderUid,
+) -> Result<Vec<OrderEvent>, sqlx::Error> {
+    const QUERY: &str = "SELECT * FROM order_events WHERE order_uid = $1 ORDER BY timestamp ASC";
+    sqlx::query_as(QUERY).bind(order).fetch_all(ex).await
+}
+
+/// Fetches the most recent event of an order.
+pub async fn fetch_latest_order_event(
+    ex: &mut PgConnection,
+    order: &OrderUid,
+) -> Result<Option<OrderEvent>, sqlx::Error> {
+    const QUERY: &str =
+        "SELECT * FROM order_events WHERE order_uid = $1 ORDER BY timestamp DESC LIMIT 1";
+    sqlx::query_as(QUERY).bind(order).fetch_optional(ex).await
+}
+
+/// Fetches the most recent event of each of the given orders. Orders without
+/// any events are missing from the result.
+pub async fn fetch_latest_order_events(
+    ex: &mut PgConnection,
+    orders: &[OrderUid],
+) -> Result<Vec<OrderEvent>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT DISTINCT ON (order_uid) *
+FROM order_events
+WHERE order_uid = ANY($1)
+ORDER BY order_uid, timestamp DESC
+"#;
+    sqlx::query_as(QUERY).bind(orders).fetch_all(ex).await
+}
+
+/// Deletes all events registered before the given timestamp and returns how
+/// many were deleted.
+pub async fn delete_order_events_before(
+    ex: &mut PgConnection,
+    timestamp: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    const QUERY: &str = "DELETE FROM order_events WHERE timestamp < $1";
+    sqlx::query(QUERY)
+        .bind(timestamp)
+        .execute(ex)
+        .await
+        .map(|result| result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::byte_array::ByteArray,
+        chrono::TimeZone,
+        sqlx::Connection,
+        std::ops::DerefMut,
+    };
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_insert_order_events() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+        sqlx::query("TRUNCATE order_events")
+            .execute(db.deref_mut())
+            .await
+            .unwrap();
+
+        let timestamp = Utc::now();
+        let events: Vec<_> = (0..1000u16)
+            .map(|i| {
+                let mut uid = [0; 56];
+                uid[..2].copy_from_slice(&i.to_be_bytes());
+                OrderEvent {
+                    order_uid: ByteArray(uid),
+                    timestamp,
+                    label: OrderEventLabel::Created,
+                }
+            })
+            .collect();
+        insert_order_events(&mut db, &events).await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM order_events")
+            .fetch_one(db.deref_mut())
+            .await
+            .unwrap();
+        assert_eq!(count, 1000);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_fetch_order_events() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        sqlx::query("TRUNCATE order_events")
+            .execute(db.deref_mut())
+            .await
+            .unwrap();
+
+        let uid = ByteArray([1; 56]);
+        assert!(fetch_order_events(&mut db, &uid).await.unwrap().is_empty());
+        assert_eq!(fetch_latest_order_event(&mut db, &uid).await.unwrap(), None);
+        assert!(fetch_latest_order_events(&mut db, &[uid])
+            .await
+            .unwrap()
+            .is_empty());
+
+        // postgres only stores timestamps with microsecond precision
+        let now = Utc.timestamp_millis_opt(1_000_000).unwrap();
+        let created = OrderEvent {
+            order_uid: uid,
+            timestamp: now - chrono::Duration::seconds(10),
+            label: OrderEventLabel::Created,
+        };
+        let traded = OrderEvent {
+            order_uid: uid,
+            timestamp: now,
+            label: OrderEventLabel::Traded,
+        };
+        let other_order = OrderEvent {
+            order_uid: ByteArray([2; 56]),
+            timestamp: now,
+            label: OrderEventLabel::Cancelled,
+        };
+        insert_order_events(&mut db, &[traded, other_order, created])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fetch_order_events(&mut db, &uid).await.unwrap(),
+            vec![created, traded]
+        );
+        assert_eq!(
+            fetch_latest_order_event(&mut db, &uid).await.unwrap(),
+            Some(traded)
+        );
+
+        let mut latest = fetch_latest_order_events(&mut db, &[uid, other_order.order_uid])
+            .await
+            .unwrap();
+        latest.sort_by_key(|event| event.order_uid.0);
+        assert_eq!(latest, vec![traded, other_order]);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_delete_order_events_before() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        sqlx::query("TRUNCATE order_events")
+            .execute(db.deref_mut())
+            .await
+            .unwrap();
+
+        let old = Utc.timestamp_millis_opt(1_000_000).unwrap();
+        let new = Utc.timestamp_millis_opt(2_000_000).unwrap();
+        let event = |uid: u8, timestamp| OrderEvent {
+            order_uid: ByteArray([uid; 56]),
+            timestamp,
+            label: OrderEventLabel::Created,
+        };
+        insert_order_events(&mut db, &[event(1, old), event(2, old), event(3, new)])
+            .await
+            .unwrap();
+
+        let deleted = delete_order_events_before(&mut db, new).await.unwrap();
+        assert_eq!(deleted, 2);
+        assert!(fetch_order_events(&mut db, &ByteArray([1; 56]))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            fetch_order_events(&mut db, &ByteArray([3; 56]))
+                .await
+                .unwrap(),
+            vec![event(3, new)]
+        );
+    }
+}
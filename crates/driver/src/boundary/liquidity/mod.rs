@@ -42,6 +42,7 @@ fn cache_config() -> CacheConfig {
         maximum_recent_block_age: 4,
         max_retries: 5,
         delay_between_retries: Duration::from_secs(1),
+        negative_ttl: None,
     }
 }
 
@@ -212,6 +212,7 @@ impl AccessListEstimating for AccessListEstimator {
         &self,
         txs: &[TransactionBuilder<DynTransport>],
         _partial_access_list: Option<AccessList>,
+        _at_block: Option<u64>,
     ) -> Result<Vec<Result<AccessList>>> {
         let mut result = Vec::new();
         result.resize_with(txs.len(), || Ok(self.0.clone().into()));
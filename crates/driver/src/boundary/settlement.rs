@@ -33,6 +33,7 @@ use {
         signature::EcdsaSignature,
         DomainSeparator,
     },
+    num::ToPrimitive,
     shared::{
         external_prices::ExternalPrices,
         http_solver::{
@@ -53,6 +54,10 @@ use {
     std::sync::Arc,
 };
 
+/// How many token allowances are fetched concurrently when encoding the
+/// approvals of a solution.
+const MAX_CONCURRENT_ALLOWANCE_REQUESTS: usize = 10;
+
 #[derive(Debug, Clone)]
 pub struct Settlement {
     pub(super) inner: solver::settlement::Settlement,
@@ -117,7 +122,9 @@ impl Settlement {
             settlement.with_liquidity(&boundary_limit_order, execution)?;
         }
 
-        let approvals = solution.approvals(eth).await?;
+        let approvals = solution
+            .approvals(eth, MAX_CONCURRENT_ALLOWANCE_REQUESTS)
+            .await?;
         for approval in approvals {
             settlement
                 .encoder
@@ -219,19 +226,7 @@ impl Settlement {
         eth: &Ethereum,
         auction: &competition::Auction,
     ) -> Result<score::Quality, boundary::Error> {
-        let prices = ExternalPrices::try_from_auction_prices(
-            eth.contracts().weth().address(),
-            auction
-                .tokens()
-                .iter()
-                .filter_map(|token| {
-                    token
-                        .price
-                        .map(|price| (token.address.into(), price.into()))
-                })
-                .collect(),
-        )?;
-
+        let prices = external_prices(eth, auction)?;
         let surplus = self.inner.total_surplus(&prices);
         let solver_fees = self.inner.total_solver_fees(&prices);
         let quality = surplus + solver_fees;
@@ -239,6 +234,19 @@ impl Settlement {
         Ok(eth::U256::from_big_rational(&quality)?.into())
     }
 
+    /// Total surplus of the settlement denominated in wei of the native token.
+    pub fn surplus(
+        &self,
+        eth: &Ethereum,
+        auction: &competition::Auction,
+    ) -> Result<f64, boundary::Error> {
+        let prices = external_prices(eth, auction)?;
+        self.inner
+            .total_surplus(&prices)
+            .to_f64()
+            .context("surplus not representable as f64")
+    }
+
     pub fn merge(self, other: Self) -> Result<Self> {
         self.inner.merge(other.inner).map(|inner| Self {
             inner,
@@ -247,6 +255,24 @@ impl Settlement {
     }
 }
 
+fn external_prices(
+    eth: &Ethereum,
+    auction: &competition::Auction,
+) -> Result<ExternalPrices, boundary::Error> {
+    ExternalPrices::try_from_auction_prices(
+        eth.contracts().weth().address(),
+        auction
+            .tokens()
+            .iter()
+            .filter_map(|token| {
+                token
+                    .price
+                    .map(|price| (token.address.into(), price.into()))
+            })
+            .collect(),
+    )
+}
+
 fn to_boundary_order(order: &competition::Order) -> Order {
     Order {
         data: OrderData {
@@ -15,7 +15,10 @@ use {
     },
     futures::{stream::FuturesUnordered, Stream, StreamExt},
     itertools::Itertools,
-    std::{collections::HashSet, sync::Mutex},
+    std::{
+        collections::{HashMap, HashSet},
+        sync::Mutex,
+    },
     tap::TapFallible,
 };
 
@@ -96,6 +99,7 @@ impl Competition {
                 notify::empty_solution(&self.solver, auction.id(), solution.id());
                 false
             } else {
+                observe::nonempty_solution(self.solver.name());
                 true
             }
         });
@@ -171,7 +175,15 @@ impl Competition {
         let (mut score, settlement) = scores
             .into_iter()
             .max_by_key(|(score, _)| score.to_owned())
-            .map(|(score, settlement)| (Solved { score }, settlement))
+            .map(|(score, settlement)| {
+                let solved = Solved {
+                    score,
+                    gas: settlement.gas.estimate,
+                    surplus: settlement.boundary.surplus(&self.eth, auction).ok(),
+                    trades: settlement.executions().ok(),
+                };
+                (solved, settlement)
+            })
             .unzip();
 
         *self.settlement.lock().unwrap() = settlement.clone();
@@ -335,6 +347,14 @@ async fn merge_settlements(
 #[derive(Debug)]
 pub struct Solved {
     pub score: Score,
+    /// The estimated gas used by the winning settlement.
+    pub gas: eth::Gas,
+    /// The surplus of the winning settlement denominated in wei of the native
+    /// token, if it could be computed.
+    pub surplus: Option<f64>,
+    /// The amounts executed for each order of the winning settlement, if they
+    /// could be computed.
+    pub trades: Option<HashMap<order::Uid, solution::trade::Execution>>,
 }
 
 /// Winning solution information revealed to the protocol by the driver before
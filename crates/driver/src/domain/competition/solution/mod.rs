@@ -13,9 +13,9 @@ use {
             Simulator,
         },
     },
-    futures::future::try_join_all,
+    futures::{stream, StreamExt, TryStreamExt},
     itertools::Itertools,
-    std::collections::{BTreeSet, HashMap},
+    std::collections::{BTreeSet, HashMap, HashSet},
     thiserror::Error,
 };
 
@@ -39,9 +39,11 @@ pub struct Solution {
     solver: Solver,
     score: SolverScore,
     weth: eth::WethAddress,
+    gas: Option<eth::Gas>,
 }
 
 impl Solution {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: Id,
         trades: Vec<Trade>,
@@ -50,6 +52,7 @@ impl Solution {
         solver: Solver,
         score: SolverScore,
         weth: eth::WethAddress,
+        gas: Option<eth::Gas>,
     ) -> Result<Self, InvalidClearingPrices> {
         let solution = Self {
             id,
@@ -59,6 +62,7 @@ impl Solution {
             solver,
             score,
             weth,
+            gas,
         };
 
         // Check that the solution includes clearing prices for all user trades.
@@ -96,18 +100,35 @@ impl Solution {
         &self.score
     }
 
-    /// Approval interactions necessary for encoding the settlement.
+    /// The gas the solver estimated for executing this solution, if it
+    /// provided an estimate.
+    pub fn gas(&self) -> Option<eth::Gas> {
+        self.gas
+    }
+
+    /// The set of tokens bought or sold by any of the trades of this solution,
+    /// including JIT trades.
+    pub fn traded_tokens(&self) -> HashSet<eth::TokenAddress> {
+        traded_tokens(&self.trades)
+    }
+
+    /// Approval interactions necessary for encoding the settlement. At most
+    /// `max_concurrent_requests` allowances are fetched at the same time.
     pub async fn approvals(
         &self,
         eth: &Ethereum,
+        max_concurrent_requests: usize,
     ) -> Result<impl Iterator<Item = eth::allowance::Approval>, Error> {
         let settlement_contract = &eth.contracts().settlement();
-        let allowances = try_join_all(self.allowances().map(|required| async move {
-            eth.erc20(required.0.token)
-                .allowance(settlement_contract.address().into(), required.0.spender)
-                .await
-                .map(|existing| (required, existing))
-        }))
+        let allowances = try_join_bounded(
+            self.allowances().map(|required| async move {
+                eth.erc20(required.0.token)
+                    .allowance(settlement_contract.address().into(), required.0.spender)
+                    .await
+                    .map(|existing| (required, existing))
+            }),
+            max_concurrent_requests,
+        )
         .await?;
         let approvals = allowances.into_iter().filter_map(|(required, existing)| {
             required
@@ -226,6 +247,20 @@ impl Solution {
         Ok(prices.collect_vec())
     }
 
+    /// Ensures that the solution doesn't contain any zero clearing prices. A
+    /// zero price is never meaningful and would otherwise only surface much
+    /// later as failing fee and surplus computations.
+    pub fn validate_prices(&self) -> Result<(), Error> {
+        validate_prices(&self.prices)
+    }
+
+    /// Ensures that no order is fulfilled by more than one trade. Executing
+    /// the same order twice would double count its fill and break the asset
+    /// flow of the settlement.
+    pub fn validate_unique_orders(&self) -> Result<(), Error> {
+        validate_unique_orders(&self.trades)
+    }
+
     /// Clearing price for the given token.
     pub fn clearing_price(&self, token: eth::TokenAddress) -> Option<eth::U256> {
         // The clearing price of ETH is equal to WETH.
@@ -243,10 +278,76 @@ impl std::fmt::Debug for Solution {
             .field("interactions", &self.interactions)
             .field("solver", &self.solver.name())
             .field("score", &self.score)
+            .field("gas", &self.gas)
             .finish()
     }
 }
 
+/// Like [`futures::future::try_join_all`] but with at most `limit` futures
+/// running at the same time. The results keep the order of the futures.
+async fn try_join_bounded<T, E>(
+    futures: impl IntoIterator<Item = impl std::future::Future<Output = Result<T, E>>>,
+    limit: usize,
+) -> Result<Vec<T>, E> {
+    stream::iter(futures)
+        .buffered(limit.max(1))
+        .try_collect()
+        .await
+}
+
+fn traded_tokens(trades: &[Trade]) -> HashSet<eth::TokenAddress> {
+    trades
+        .iter()
+        .flat_map(|trade| match trade {
+            Trade::Fulfillment(fulfillment) => [
+                fulfillment.order().sell.token,
+                fulfillment.order().buy.token,
+            ],
+            Trade::Jit(jit) => [jit.order().sell.token, jit.order().buy.token],
+        })
+        .collect()
+}
+
+fn validate_prices(prices: &HashMap<eth::TokenAddress, eth::U256>) -> Result<(), Error> {
+    match prices.iter().find(|(_, price)| price.is_zero()) {
+        Some((&token, _)) => Err(Error::InvalidClearingPrice { token }),
+        None => Ok(()),
+    }
+}
+
+fn validate_unique_orders(trades: &[Trade]) -> Result<(), Error> {
+    let mut uids = HashSet::new();
+    for trade in trades {
+        if let Trade::Fulfillment(fulfillment) = trade {
+            let uid = fulfillment.order().uid;
+            if !uids.insert(uid) {
+                return Err(Error::DuplicateOrder { uid });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The simulated gas of a settlement may be at most this many times the gas
+/// claimed by the solver.
+const MAX_GAS_UNDERESTIMATION_FACTOR: u64 = 2;
+
+/// Ensures that the solver didn't wildly underestimate the gas of its
+/// solution, which would point to a bug in the solver. Solutions without a gas
+/// estimate are always accepted.
+fn validate_gas(claimed: Option<eth::Gas>, simulated: eth::Gas) -> Result<(), Error> {
+    let Some(claimed) = claimed else {
+        return Ok(());
+    };
+    let max = claimed
+        .0
+        .saturating_mul(MAX_GAS_UNDERESTIMATION_FACTOR.into());
+    if simulated.0 > max {
+        return Err(Error::GasUnderestimated { claimed, simulated });
+    }
+    Ok(())
+}
+
 /// The time limit passed to the solver for solving an auction.
 #[derive(Debug, Clone, Copy)]
 pub struct SolverTimeout(chrono::Duration);
@@ -264,6 +365,21 @@ impl SolverTimeout {
     pub fn reduce(self, duration: chrono::Duration) -> Self {
         Self(self.0 - duration)
     }
+
+    /// The time left of this timeout at `now` if solving started at
+    /// `started_at`. Zero once the timeout has passed.
+    pub fn remaining(
+        self,
+        started_at: std::time::Instant,
+        now: std::time::Instant,
+    ) -> std::time::Duration {
+        let elapsed = now.saturating_duration_since(started_at);
+        let elapsed = chrono::Duration::from_std(elapsed).unwrap_or(chrono::Duration::max_value());
+        self.0
+            .checked_sub(&elapsed)
+            .and_then(|remaining| remaining.to_std().ok())
+            .unwrap_or_default()
+    }
 }
 
 impl From<std::time::Duration> for SolverTimeout {
@@ -297,6 +413,20 @@ impl From<Id> for u64 {
     }
 }
 
+impl std::fmt::Display for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for Id {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("blockchain error: {0:?}")]
@@ -317,8 +447,189 @@ pub enum Error {
     SolverAccountInsufficientBalance(eth::Ether),
     #[error("attempted to merge settlements generated by different solvers")]
     DifferentSolvers,
+    #[error("invalid clearing price for token {token:?}")]
+    InvalidClearingPrice { token: eth::TokenAddress },
+    #[error("order {uid:?} is fulfilled by more than one trade")]
+    DuplicateOrder { uid: order::Uid },
+    #[error("solver claimed {claimed:?} gas but the settlement uses {simulated:?}")]
+    GasUnderestimated {
+        claimed: eth::Gas,
+        simulated: eth::Gas,
+    },
 }
 
 #[derive(Debug, Error)]
 #[error("invalid clearing prices")]
 pub struct InvalidClearingPrices;
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::util};
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::H160([byte; 20]).into()
+    }
+
+    fn asset(byte: u8) -> eth::Asset {
+        eth::Asset {
+            token: token(byte),
+            amount: eth::U256::from(1_000).into(),
+        }
+    }
+
+    fn signature() -> order::Signature {
+        order::Signature {
+            scheme: order::signature::Scheme::PreSign,
+            data: Default::default(),
+            signer: Default::default(),
+        }
+    }
+
+    fn market_order(uid: u8) -> competition::Order {
+        competition::Order {
+            uid: [uid; order::UID_LEN].into(),
+            receiver: Default::default(),
+            valid_to: util::Timestamp(u32::MAX),
+            buy: asset(2),
+            sell: asset(1),
+            side: order::Side::Sell,
+            fee: Default::default(),
+            kind: order::Kind::Market,
+            app_data: Default::default(),
+            partial: order::Partial::No,
+            pre_interactions: Default::default(),
+            post_interactions: Default::default(),
+            sell_token_balance: order::SellTokenBalance::Erc20,
+            buy_token_balance: order::BuyTokenBalance::Erc20,
+            signature: signature(),
+        }
+    }
+
+    fn fulfillment(order: competition::Order) -> trade::Fulfillment {
+        let executed = order.target();
+        trade::Fulfillment::new(order, executed, trade::Fee::Static).unwrap()
+    }
+
+    #[test]
+    fn traded_tokens_include_jit_trades() {
+        let fulfillment = fulfillment(market_order(0));
+
+        let jit = order::Jit {
+            sell: asset(2),
+            buy: asset(3),
+            fee: Default::default(),
+            receiver: Default::default(),
+            valid_to: util::Timestamp(u32::MAX),
+            app_data: Default::default(),
+            side: order::Side::Sell,
+            partially_fillable: false,
+            sell_token_balance: order::SellTokenBalance::Erc20,
+            buy_token_balance: order::BuyTokenBalance::Erc20,
+            signature: signature(),
+        };
+        let executed = jit.target();
+        let jit = trade::Jit::new(jit, executed).unwrap();
+
+        assert_eq!(
+            traded_tokens(&[Trade::Fulfillment(fulfillment), Trade::Jit(jit)]),
+            HashSet::from([token(1), token(2), token(3)])
+        );
+        assert!(traded_tokens(&[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn bounds_concurrent_requests() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let request = |i: usize| {
+            let (running, max_running) = (&running, &max_running);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok::<_, ()>(i)
+            }
+        };
+
+        let results = try_join_bounded((0..50).map(request), 10).await.unwrap();
+        assert_eq!(results, (0..50).collect_vec());
+        assert_eq!(max_running.load(Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn id_round_trips_through_string() {
+        let id: Id = "42".parse().unwrap();
+        assert_eq!(id, Id(42));
+        assert_eq!(id.to_string(), "42");
+        assert!("-1".parse::<Id>().is_err());
+    }
+
+    #[test]
+    fn rejects_zero_clearing_prices() {
+        let prices = HashMap::from([(token(1), 1.into()), (token(2), 1_000.into())]);
+        assert!(validate_prices(&prices).is_ok());
+
+        let prices = HashMap::from([(token(1), 1.into()), (token(2), 0.into())]);
+        assert!(matches!(
+            validate_prices(&prices),
+            Err(Error::InvalidClearingPrice { token: invalid }) if invalid == token(2)
+        ));
+    }
+
+    #[test]
+    fn rejects_underestimated_gas() {
+        assert!(validate_gas(None, 1_000_000.into()).is_ok());
+        assert!(validate_gas(Some(100_000.into()), 90_000.into()).is_ok());
+        assert!(validate_gas(Some(100_000.into()), 200_000.into()).is_ok());
+        assert!(matches!(
+            validate_gas(Some(100_000.into()), 200_001.into()),
+            Err(Error::GasUnderestimated { claimed, simulated })
+                if claimed.0 == 100_000.into() && simulated.0 == 200_001.into()
+        ));
+    }
+
+    #[test]
+    fn rejects_duplicate_orders() {
+        let trades = [
+            Trade::Fulfillment(fulfillment(market_order(1))),
+            Trade::Fulfillment(fulfillment(market_order(2))),
+        ];
+        assert!(validate_unique_orders(&trades).is_ok());
+
+        let trades = [
+            Trade::Fulfillment(fulfillment(market_order(1))),
+            Trade::Fulfillment(fulfillment(market_order(2))),
+            Trade::Fulfillment(fulfillment(market_order(1))),
+        ];
+        assert!(matches!(
+            validate_unique_orders(&trades),
+            Err(Error::DuplicateOrder { uid }) if uid == market_order(1).uid
+        ));
+    }
+
+    #[test]
+    fn remaining_solver_timeout() {
+        let timeout = SolverTimeout::from(std::time::Duration::from_secs(10));
+        let started_at = std::time::Instant::now();
+
+        assert_eq!(
+            timeout.remaining(started_at, started_at),
+            std::time::Duration::from_secs(10)
+        );
+        assert_eq!(
+            timeout.remaining(started_at, started_at + std::time::Duration::from_secs(4)),
+            std::time::Duration::from_secs(6)
+        );
+        assert_eq!(
+            timeout.remaining(started_at, started_at + std::time::Duration::from_secs(10)),
+            std::time::Duration::ZERO
+        );
+        assert_eq!(
+            timeout.remaining(started_at, started_at + std::time::Duration::from_secs(11)),
+            std::time::Duration::ZERO
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use {
-    super::{Error, Solution},
+    super::{trade, Error, Solution},
     crate::{
         boundary,
         domain::{
@@ -72,6 +72,13 @@ impl Settlement {
             return Err(Error::NonBufferableTokensUsed(untrusted_tokens));
         }
 
+        // Zero clearing prices would lead to division by zero errors in the fee
+        // and surplus computations.
+        solution.validate_prices()?;
+
+        // Fulfilling an order more than once would double count its fill.
+        solution.validate_unique_orders()?;
+
         // Encode the solution into a settlement.
         let boundary = boundary::Settlement::encode(eth, &solution, auction).await?;
         Self::new(
@@ -134,6 +141,20 @@ impl Settlement {
             Internalization::Enable,
         )
         .await?;
+
+        // Solvers can provide their own gas estimate, which should roughly
+        // match the simulation. Merged settlements are only checked if all of
+        // their solutions have an estimate.
+        let claimed = solutions
+            .values()
+            .try_fold(eth::Gas::default(), |acc, solution| {
+                Some(acc + solution.gas()?)
+            });
+        if let Some(claimed) = claimed {
+            observe::gas_estimates(claimed, gas);
+        }
+        super::validate_gas(claimed, gas)?;
+
         let price = eth.gas_price().await?;
         let gas = Gas::new(gas, price);
 
@@ -311,6 +332,20 @@ impl Settlement {
             .collect()
     }
 
+    /// The amounts executed for each of the settled user orders.
+    pub fn executions(
+        &self,
+    ) -> Result<HashMap<order::Uid, trade::Execution>, trade::ExecutionError> {
+        self.solutions
+            .values()
+            .flat_map(|solution| {
+                solution
+                    .user_trades()
+                    .map(move |trade| Ok((trade.order().uid, trade.execution(solution)?)))
+            })
+            .collect()
+    }
+
     /// Settlements have valid notify ID only if they are originated from a
     /// single solution. Otherwise, for merged settlements, no notifications
     /// are sent, therefore, notify id is None.
@@ -1,6 +1,9 @@
-use crate::domain::{
-    competition::{self, order},
-    eth,
+use crate::{
+    domain::{
+        competition::{self, order},
+        eth,
+    },
+    util::conv::u256::U256Ext,
 };
 
 /// A trade which executes an order as part of this solution.
@@ -76,6 +79,46 @@ impl Fulfillment {
         self.executed
     }
 
+    /// The amounts executed by this fulfillment at the clearing prices of the
+    /// given solution.
+    pub fn execution(&self, solution: &super::Solution) -> Result<Execution, ExecutionError> {
+        let sell_price = solution
+            .clearing_price(self.order.sell.token)
+            .ok_or(ExecutionError::ClearingPriceMissing(self.order.sell.token))?;
+        let buy_price = solution
+            .clearing_price(self.order.buy.token)
+            .ok_or(ExecutionError::ClearingPriceMissing(self.order.buy.token))?;
+        let executed = self.executed.0;
+
+        let (sell, buy) = match self.order.side {
+            order::Side::Sell => (
+                executed,
+                executed
+                    .checked_mul(sell_price)
+                    .and_then(|amount| amount.checked_div(buy_price))
+                    .ok_or(ExecutionError::Overflow)?,
+            ),
+            order::Side::Buy => (
+                executed
+                    .checked_mul(buy_price)
+                    .and_then(|amount| amount.checked_ceil_div(&sell_price))
+                    .ok_or(ExecutionError::Overflow)?,
+                executed,
+            ),
+        };
+
+        Ok(Execution {
+            sell: eth::Asset {
+                token: self.order.sell.token,
+                amount: sell.into(),
+            },
+            buy: eth::Asset {
+                token: self.order.buy.token,
+                amount: buy.into(),
+            },
+        })
+    }
+
     /// Returns the solver fee that should be considered as collected when
     /// scoring a solution.
     pub fn solver_fee(&self) -> order::SellAmount {
@@ -3,5 +3,5 @@ mod solved;
 
 pub use {
     auction::{Auction, Error as AuctionError},
-    solved::Solved,
+    solved::{Params, Solved},
 };
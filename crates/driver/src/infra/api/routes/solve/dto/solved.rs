@@ -1,18 +1,27 @@
 use {
     crate::{
-        domain::{competition, eth},
+        domain::{competition, competition::order, eth},
         infra::Solver,
         util::serialize,
     },
-    serde::Serialize,
+    serde::{Deserialize, Serialize},
     serde_with::serde_as,
 };
 
+/// Query parameters of the `/solve` endpoint.
+#[derive(Debug, Default, Deserialize)]
+pub struct Params {
+    /// Whether the executed amounts of the solved orders should be included
+    /// in the response.
+    #[serde(default)]
+    include_trades: bool,
+}
+
 impl Solved {
-    pub fn new(solved: Option<competition::Solved>, solver: &Solver) -> Self {
+    pub fn new(solved: Option<competition::Solved>, solver: &Solver, params: &Params) -> Self {
         let solutions = solved
             .into_iter()
-            .map(|solved| Solution::new(0, solved, solver))
+            .map(|solved| Solution::new(0, solved, solver, params.include_trades))
             .collect();
         Self { solutions }
     }
@@ -26,11 +35,30 @@ pub struct Solved {
 }
 
 impl Solution {
-    pub fn new(solution_id: u64, solved: competition::Solved, solver: &Solver) -> Self {
+    pub fn new(
+        solution_id: u64,
+        solved: competition::Solved,
+        solver: &Solver,
+        include_trades: bool,
+    ) -> Self {
+        let trades = solved.trades.filter(|_| include_trades).map(|trades| {
+            trades
+                .into_iter()
+                .map(|(uid, execution)| Trade {
+                    order: uid.into(),
+                    executed_sell: execution.sell.amount.into(),
+                    executed_buy: execution.buy.amount.into(),
+                })
+                .collect()
+        });
         Self {
             solution_id,
             score: solved.score.0.get(),
             submission_address: solver.address().into(),
+            solver: solver.name().to_string(),
+            gas: solved.gas.0.try_into().ok(),
+            surplus: solved.surplus,
+            trades,
         }
     }
 }
@@ -46,4 +74,27 @@ pub struct Solution {
     #[serde_as(as = "serialize::U256")]
     score: eth::U256,
     submission_address: eth::H160,
+    /// Name of the solver which produced the solution.
+    solver: String,
+    /// Estimated gas used by the solution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas: Option<u64>,
+    /// Surplus of the solution denominated in wei of the native token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    surplus: Option<f64>,
+    /// The orders filled by the solution. Only included if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trades: Option<Vec<Trade>>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trade {
+    #[serde_as(as = "serialize::Hex")]
+    order: [u8; order::UID_LEN],
+    #[serde_as(as = "serialize::U256")]
+    executed_sell: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    executed_buy: eth::U256,
 }
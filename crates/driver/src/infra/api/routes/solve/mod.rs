@@ -16,6 +16,7 @@ pub(in crate::infra::api) fn solve(router: axum::Router<State>) -> axum::Router<
 
 async fn route(
     state: axum::extract::State<State>,
+    params: axum::extract::Query<dto::Params>,
     auction: axum::Json<dto::Auction>,
 ) -> Result<axum::Json<dto::Solved>, (hyper::StatusCode, axum::Json<Error>)> {
     let auction_id = auction.id();
@@ -32,7 +33,11 @@ async fn route(
         let competition = state.competition();
         let result = competition.solve(&auction).await;
         observe::solved(state.solver().name(), &result);
-        Ok(axum::Json(dto::Solved::new(result?, &competition.solver)))
+        Ok(axum::Json(dto::Solved::new(
+            result?,
+            &competition.solver,
+            &params,
+        )))
     };
 
     handle_request
@@ -80,6 +80,9 @@ pub fn encoding_failed(
         solution::Error::Execution(_) => return,
         solution::Error::FailingInternalization => return,
         solution::Error::DifferentSolvers => return,
+        solution::Error::InvalidClearingPrice { .. } => return,
+        solution::Error::DuplicateOrder { .. } => return,
+        solution::Error::GasUnderestimated { .. } => return,
     };
 
     solver.notify(auction_id, Some(solution_id), notification);
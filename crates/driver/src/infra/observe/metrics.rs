@@ -4,6 +4,12 @@ pub struct Metrics {
     /// Reasons for dropped solutions.
     #[metric(labels("solver", "reason"))]
     pub dropped_solutions: prometheus::IntCounterVec,
+    /// Empty solutions returned by solvers.
+    #[metric(name = "solver_empty_solutions_total", labels("solver"))]
+    pub empty_solutions: prometheus::IntCounterVec,
+    /// Non-empty solutions returned by solvers.
+    #[metric(name = "solver_nonempty_solutions_total", labels("solver"))]
+    pub nonempty_solutions: prometheus::IntCounterVec,
     /// The results of the solving process.
     #[metric(labels("solver", "result"))]
     pub solutions: prometheus::IntCounterVec,
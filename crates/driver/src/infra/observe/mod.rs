@@ -63,7 +63,7 @@ pub fn fetching_liquidity_failed(err: &boundary::Error) {
 }
 
 pub fn duplicated_solution_id(solver: &solver::Name, id: solution::Id) {
-    tracing::debug!(?id, "discarded solution: duplicated id");
+    tracing::debug!(%id, "discarded solution: duplicated id");
     metrics::get()
         .dropped_solutions
         .with_label_values(&[solver.as_str(), "DuplicateId"])
@@ -86,6 +86,18 @@ pub fn empty_solution(solver: &solver::Name, id: solution::Id) {
         .dropped_solutions
         .with_label_values(&[solver.as_str(), "EmptySolution"])
         .inc();
+    metrics::get()
+        .empty_solutions
+        .with_label_values(&[solver.as_str()])
+        .inc();
+}
+
+/// Observe that a solution is not empty.
+pub fn nonempty_solution(solver: &solver::Name) {
+    metrics::get()
+        .nonempty_solutions
+        .with_label_values(&[solver.as_str()])
+        .inc();
 }
 
 // Observe that postprocessing (encoding & merging) of solutions is about to
@@ -372,6 +384,11 @@ pub fn order_excluded_from_auction(
     tracing::trace!(uid=?order.uid, ?reason, "order excluded from auction");
 }
 
+/// Observe the gas estimate of a solver next to the simulated gas.
+pub fn gas_estimates(claimed: eth::Gas, simulated: eth::Gas) {
+    tracing::debug!(claimed = ?claimed.0, simulated = ?simulated.0, "solver gas estimate");
+}
+
 /// Observe that a settlement was simulated
 pub fn simulated(eth: &Ethereum, tx: &eth::Tx, gas: &Result<Gas, simulator::Error>) {
     let block: eth::BlockNo = eth.current_block().borrow().number.into();
@@ -205,6 +205,7 @@ impl Solutions {
                         }
                     },
                     weth,
+                    solution.gas.map(Into::into),
                 )
                 .map_err(|competition::solution::InvalidClearingPrices| {
                     super::Error("invalid clearing prices")
@@ -231,6 +232,9 @@ pub struct Solution {
     trades: Vec<Trade>,
     interactions: Vec<Interaction>,
     score: Score,
+    /// The solver's own estimate of the gas used by the solution.
+    #[serde_as(as = "Option<serialize::U256>")]
+    gas: Option<eth::U256>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -778,8 +778,11 @@ impl SolveOk {
         assert_eq!(solutions.len(), 1);
         let solution = solutions[0].clone();
         assert!(solution.is_object());
-        assert_eq!(solution.as_object().unwrap().len(), 3);
+        assert_eq!(solution.as_object().unwrap().len(), 6);
         assert!(solution.get("score").is_some());
+        assert!(solution.get("solver").is_some());
+        assert!(solution.get("gas").is_some());
+        assert!(solution.get("surplus").is_some());
         let score = solution.get("score").unwrap().as_str().unwrap();
         eth::U256::from_dec_str(score).unwrap()
     }
@@ -1,21 +1,37 @@
 use {
     crate::{
         nodes::NODE_HOST,
-        setup::{wait_for_condition, Contracts, TIMEOUT},
+        setup::{wait_for_condition, Contracts, OnchainComponents, TestAccount, TIMEOUT},
     },
+    anyhow::{anyhow, Context, Result},
     clap::Parser,
-    ethcontract::{H160, H256},
+    database::{byte_array::ByteArray, order_events::OrderEventLabel},
+    ethcontract::{PrivateKey, H160, H256},
     model::{
         app_data::{AppDataDocument, AppDataHash},
-        auction::AuctionWithId,
-        order::{Order, OrderCreation, OrderUid},
+        auction::{AuctionId, AuctionWithId},
+        order::{
+            CancellationPayload,
+            Order,
+            OrderCancellation,
+            OrderCancellations,
+            OrderCreation,
+            OrderStatus,
+            OrderUid,
+            SignedOrderCancellations,
+        },
         quote::{OrderQuoteRequest, OrderQuoteResponse},
+        signature::EcdsaSigningScheme,
         solver_competition::SolverCompetitionAPI,
         trade::Trade,
     },
-    reqwest::{Client, StatusCode, Url},
+    reqwest::{Client, RequestBuilder, StatusCode, Url},
+    secp256k1::SecretKey,
+    serde::{de::DeserializeOwned, Serialize},
     sqlx::Connection,
-    std::time::Duration,
+    std::{collections::HashSet, sync::Mutex, thread::JoinHandle, time::Duration},
+    tokio::sync::oneshot,
+    web3::signing::SecretKeyRef,
 };
 
 pub const API_HOST: &str = "http://127.0.0.1:8080";
@@ -34,24 +50,47 @@ pub struct Services<'a> {
     contracts: &'a Contracts,
     http: Client,
     db: Db,
+    /// The autopilot started by [`Services::start_autopilot`].
+    autopilot: Mutex<Option<AutopilotHandle>>,
+    price_estimators: PriceEstimators,
 }
 
 impl<'a> Services<'a> {
     pub async fn new(contracts: &'a Contracts) -> Services<'a> {
+        Self::new_with_timeout(contracts, Duration::from_secs(10)).await
+    }
+
+    /// Like [`Services::new`] but all requests made by the helper methods time
+    /// out after `timeout`.
+    pub async fn new_with_timeout(contracts: &'a Contracts, timeout: Duration) -> Services<'a> {
         Self {
             contracts,
-            http: Client::builder()
-                .timeout(Duration::from_secs(10))
-                .build()
-                .unwrap(),
+            http: Client::builder().timeout(timeout).build().unwrap(),
             db: sqlx::PgPool::connect(LOCAL_DB_URL).await.unwrap(),
+            autopilot: Default::default(),
+            price_estimators: Default::default(),
+        }
+    }
+
+    /// Makes the API and the autopilot use the given price estimators instead
+    /// of the default Baseline ones.
+    pub fn with_price_estimators(self, price_estimators: PriceEstimators) -> Self {
+        Self {
+            price_estimators,
+            ..self
         }
     }
 
-    fn api_autopilot_arguments() -> impl Iterator<Item = String> {
+    fn api_autopilot_arguments(&self) -> impl Iterator<Item = String> {
         [
-            "--price-estimators=Baseline|0x0000000000000000000000000000000000000001".to_string(),
-            "--native-price-estimators=Baseline".to_string(),
+            format!(
+                "--price-estimators={}",
+                self.price_estimators.price_estimators
+            ),
+            format!(
+                "--native-price-estimators={}",
+                self.price_estimators.native_price_estimators
+            ),
             "--amount-to-estimate-prices-with=1000000000000000000".to_string(),
             "--block-stream-poll-interval-seconds=1".to_string(),
         ]
@@ -81,7 +120,9 @@ impl<'a> Services<'a> {
         .into_iter()
     }
 
-    /// Start the autopilot service in a background task.
+    /// Start the autopilot service on a dedicated runtime in a background
+    /// thread. The autopilot can be killed again with
+    /// [`Services::stop_autopilot`].
     pub fn start_autopilot(&self, extra_args: Vec<String>) {
         let args = [
             "autopilot".to_string(),
@@ -92,11 +133,32 @@ impl<'a> Services<'a> {
         ]
         .into_iter()
         .chain(self.api_autopilot_solver_arguments())
-        .chain(Self::api_autopilot_arguments())
+        .chain(self.api_autopilot_arguments())
         .chain(extra_args);
 
         let args = autopilot::arguments::Arguments::try_parse_from(args).unwrap();
-        tokio::task::spawn(autopilot::run(args));
+        let mut autopilot = self.autopilot.lock().unwrap();
+        assert!(autopilot.is_none(), "autopilot is already running");
+        *autopilot = Some(AutopilotHandle::spawn(args));
+    }
+
+    /// Kills the autopilot started with [`Services::start_autopilot`]
+    /// together with all the background tasks it spawned (e.g. the metrics
+    /// server or the settlement event updater).
+    pub async fn stop_autopilot(&self) {
+        let autopilot = self.autopilot.lock().unwrap().take();
+        if let Some(mut autopilot) = autopilot {
+            tokio::task::spawn_blocking(move || autopilot.shutdown())
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Kills the running autopilot and starts a new one which has to resume
+    /// from the state stored in the database.
+    pub async fn restart_autopilot(&self, extra_args: Vec<String>) {
+        self.stop_autopilot().await;
+        self.start_autopilot(extra_args);
     }
 
     /// Start the api service in a background tasks.
@@ -113,7 +175,7 @@ impl<'a> Services<'a> {
         ]
         .into_iter()
         .chain(self.api_autopilot_solver_arguments())
-        .chain(Self::api_autopilot_arguments())
+        .chain(self.api_autopilot_arguments())
         .chain(extra_args.into_iter());
 
         let args = orderbook::arguments::Arguments::try_parse_from(args).unwrap();
@@ -124,19 +186,12 @@ impl<'a> Services<'a> {
 
     /// Start the solver service in a background task.
     pub fn start_old_driver(&self, private_key: &[u8; 32], extra_args: Vec<String>) {
-        let args = [
-            "solver".to_string(),
-            format!("--solver-account={}", hex::encode(private_key)),
-            "--settle-interval=1".to_string(),
-            format!("--transaction-submission-nodes={NODE_HOST}"),
-            format!("--ethflow-contract={:?}", self.contracts.ethflow.address()),
-        ]
-        .into_iter()
-        .chain(self.api_autopilot_solver_arguments())
-        .chain(extra_args);
-
-        let args = solver::arguments::Arguments::try_parse_from(args).unwrap();
-        tokio::task::spawn(solver::run::run(args));
+        self.start_drivers(vec![DriverConfig {
+            account: SolverAccount::PrivateKey(*private_key),
+            solver_url: None,
+            metrics_port: DriverConfig::DEFAULT_METRICS_PORT,
+            extra_args,
+        }]);
     }
 
     /// Start the solver service in a background task with a custom http solver
@@ -147,27 +202,61 @@ impl<'a> Services<'a> {
         solver_account: H160,
         extra_args: Vec<String>,
     ) {
-        let args = [
-            "solver".to_string(),
-            format!(
-                "--external-solvers=Custom|{}|{:#x}|false",
-                solver_url
-                    .unwrap_or("http://localhost:8000".parse().unwrap())
-                    .as_str(),
-                solver_account
-            ),
-            "--solvers=None".to_string(),
-            format!("--solver-account={:#x}", solver_account),
-            "--settle-interval=1".to_string(),
-            format!("--transaction-submission-nodes={NODE_HOST}"),
-            format!("--ethflow-contract={:?}", self.contracts.ethflow.address()),
-        ]
-        .into_iter()
-        .chain(self.api_autopilot_solver_arguments())
-        .chain(extra_args);
+        self.start_drivers(vec![DriverConfig {
+            account: SolverAccount::Address(solver_account),
+            solver_url: Some(solver_url.unwrap_or("http://localhost:8000".parse().unwrap())),
+            metrics_port: DriverConfig::DEFAULT_METRICS_PORT,
+            extra_args,
+        }]);
+    }
+
+    /// Start one solver service per config in background tasks. All of them
+    /// compete in the same auctions. They run in the same process so every
+    /// config needs a different metrics port.
+    pub fn start_drivers(&self, configs: Vec<DriverConfig>) -> Vec<DriverHandle> {
+        let metrics_ports: HashSet<_> = configs.iter().map(|config| config.metrics_port).collect();
+        assert_eq!(
+            metrics_ports.len(),
+            configs.len(),
+            "solver services need distinct metrics ports"
+        );
+        configs
+            .into_iter()
+            .map(|config| self.start_driver(config))
+            .collect()
+    }
+
+    fn start_driver(&self, config: DriverConfig) -> DriverHandle {
+        let solver = config.account.address();
+        let solvers = match &config.solver_url {
+            Some(url) => vec![
+                format!(
+                    "--external-solvers=Custom|{}|{:#x}|false",
+                    url.as_str(),
+                    solver
+                ),
+                "--solvers=None".to_string(),
+            ],
+            None => vec![],
+        };
+        let args = ["solver".to_string()]
+            .into_iter()
+            .chain(solvers)
+            .chain([
+                format!("--solver-account={}", config.account.to_arg()),
+                format!("--metrics-port={}", config.metrics_port),
+                "--settle-interval=1".to_string(),
+                format!("--transaction-submission-nodes={NODE_HOST}"),
+                format!("--ethflow-contract={:?}", self.contracts.ethflow.address()),
+            ])
+            .chain(self.api_autopilot_solver_arguments())
+            .chain(config.extra_args);
 
         let args = solver::arguments::Arguments::try_parse_from(args).unwrap();
-        tokio::task::spawn(solver::run(args));
+        DriverHandle {
+            solver,
+            task: tokio::task::spawn(solver::run(args)),
+        }
     }
 
     async fn wait_for_api_to_come_up() {
@@ -183,151 +272,271 @@ impl<'a> Services<'a> {
             .expect("waiting for API timed out");
     }
 
-    pub async fn get_auction(&self) -> AuctionWithId {
-        let response = self
-            .http
-            .get(format!("{API_HOST}{AUCTION_ENDPOINT}"))
-            .send()
-            .await
-            .unwrap();
+    /// Sends a GET request to the given API path and deserializes the JSON
+    /// response. If the response status is not `200`, return a
+    /// [`ServicesError`].
+    pub async fn get_json<T>(&self, path: &str) -> Result<T, ServicesError>
+    where
+        T: DeserializeOwned,
+    {
+        let request = self.http.get(format!("{API_HOST}{path}"));
+        let body = Self::send(request, &[StatusCode::OK]).await?;
+        Ok(serde_json::from_str(&body).unwrap())
+    }
+
+    /// Sends a POST request with a JSON body to the given API path and
+    /// deserializes the JSON response. If the response status is not
+    /// `expected`, return a [`ServicesError`].
+    pub async fn post_json<T, B>(
+        &self,
+        path: &str,
+        body: &B,
+        expected: StatusCode,
+    ) -> Result<T, ServicesError>
+    where
+        T: DeserializeOwned,
+        B: Serialize + ?Sized,
+    {
+        let request = self.http.post(format!("{API_HOST}{path}")).json(body);
+        let body = Self::send(request, &[expected]).await?;
+        Ok(serde_json::from_str(&body).unwrap())
+    }
+
+    /// Sends the request and returns the response body. If the response
+    /// status is not one of the `expected` ones, return the matching
+    /// [`ServicesError`].
+    async fn send(
+        request: RequestBuilder,
+        expected: &[StatusCode],
+    ) -> Result<String, ServicesError> {
+        let response = request.send().await.unwrap();
 
         let status = response.status();
         let body = response.text().await.unwrap();
 
-        assert_eq!(status, StatusCode::OK, "{body}");
+        if expected.contains(&status) {
+            Ok(body)
+        } else {
+            Err(ServicesError::new(status, body))
+        }
+    }
 
-        serde_json::from_str(&body).unwrap()
+    pub async fn get_auction(&self) -> AuctionWithId {
+        self.get_json(AUCTION_ENDPOINT)
+            .await
+            .unwrap_or_else(|err| panic!("{err:?}"))
+    }
+
+    /// Mines a block and waits until the autopilot built a new auction for
+    /// it. Requires that the autopilot already built at least one auction.
+    pub async fn mine_block_and_wait_for_auction(
+        &self,
+        onchain: &OnchainComponents,
+    ) -> AuctionWithId {
+        let previous = self.get_auction().await.id;
+        onchain.mint_block().await;
+        let block = onchain.web3().eth().block_number().await.unwrap().as_u64();
+
+        let is_new_auction = || async {
+            let current = self.get_auction().await;
+            current.id > previous && current.auction.block >= block
+        };
+        wait_for_condition(TIMEOUT, is_new_auction)
+            .await
+            .expect("waiting for new auction timed out");
+        self.get_auction().await
     }
 
     pub async fn get_solver_competition(
         &self,
         hash: H256,
     ) -> Result<SolverCompetitionAPI, StatusCode> {
-        let response = self
-            .http
-            .get(format!(
-                "{API_HOST}{SOLVER_COMPETITION_ENDPOINT}/by_tx_hash/{hash:?}"
-            ))
-            .send()
+        let path = format!("{SOLVER_COMPETITION_ENDPOINT}/by_tx_hash/{hash:?}");
+        self.get_json(&path).await.map_err(|err| err.status())
+    }
+
+    pub async fn get_solver_competition_by_auction_id(
+        &self,
+        id: AuctionId,
+    ) -> Result<SolverCompetitionAPI, StatusCode> {
+        self.get_json(&format!("{SOLVER_COMPETITION_ENDPOINT}/{id}"))
             .await
-            .unwrap();
+            .map_err(|err| err.status())
+    }
 
-        let status = response.status();
-        let body = response.text().await.unwrap();
+    /// Waits until the solver competition of the current auction got stored.
+    /// The autopilot writes it asynchronously so it might not be available
+    /// right after the auction was solved.
+    pub async fn wait_for_solver_competition(
+        &self,
+        timeout: Duration,
+    ) -> Result<SolverCompetitionAPI> {
+        let id = self.get_auction().await.id;
+        let is_stored = || async { self.get_solver_competition_by_auction_id(id).await.is_ok() };
+        wait_for_condition(timeout, is_stored)
+            .await
+            .with_context(|| format!("solver competition of auction {id} was not stored"))?;
 
-        match status {
-            StatusCode::OK => Ok(serde_json::from_str(&body).unwrap()),
-            code => Err(code),
-        }
+        self.get_solver_competition_by_auction_id(id)
+            .await
+            .map_err(|code| anyhow!("failed to fetch solver competition of auction {id}: {code}"))
+    }
+
+    /// Retrieve the native price of a token as estimated by the API.
+    pub async fn get_native_price(&self, token: H160) -> Result<f64, StatusCode> {
+        let response: serde_json::Value = self
+            .get_json(&format!("/api/v1/token/{token:?}/native_price"))
+            .await
+            .map_err(|err| err.status())?;
+        Ok(response["price"].as_f64().unwrap())
     }
 
     pub async fn get_trades(&self, order: &OrderUid) -> Result<Vec<Trade>, StatusCode> {
-        let url = format!("{API_HOST}/api/v1/trades?orderUid={order}");
-        let response = self.http.get(url).send().await.unwrap();
+        self.get_json(&format!("{TRADES_ENDPOINT}?orderUid={order}"))
+            .await
+            .map_err(|err| err.status())
+    }
 
-        let status = response.status();
-        let body = response.text().await.unwrap();
+    /// Repeatedly retrieves the trades of the order until one of them got
+    /// settled and returns the hash of that settlement transaction. Events only
+    /// get indexed once they are past the reorg threshold so tests usually
+    /// have to mine enough blocks for this to succeed.
+    pub async fn wait_for_settlement(&self, uid: &OrderUid, timeout: Duration) -> Result<H256> {
+        let settlement = || async {
+            self.get_trades(uid)
+                .await
+                .ok()?
+                .into_iter()
+                .find_map(|trade| trade.tx_hash)
+        };
+        let is_settled = || async { settlement().await.is_some() };
+        wait_for_condition(timeout, is_settled)
+            .await
+            .with_context(|| format!("order {uid} did not get settled"))?;
 
-        match status {
-            StatusCode::OK => Ok(serde_json::from_str(&body).unwrap()),
-            code => Err(code),
-        }
+        settlement()
+            .await
+            .with_context(|| format!("failed to fetch settlement of order {uid}"))
     }
 
     /// Create an [`Order`].
-    /// If the response status code is not `201`, return the status and the
-    /// body.
-    pub async fn create_order(
-        &self,
-        order: &OrderCreation,
-    ) -> Result<OrderUid, (StatusCode, String)> {
-        let placement = self
-            .http
-            .post(format!("{API_HOST}{ORDERS_ENDPOINT}"))
-            .json(order)
-            .send()
+    /// If the response status code is not `201`, return a [`ServicesError`].
+    pub async fn create_order(&self, order: &OrderCreation) -> Result<OrderUid, ServicesError> {
+        self.post_json(ORDERS_ENDPOINT, order, StatusCode::CREATED)
             .await
-            .unwrap();
+    }
 
-        let status = placement.status();
-        let body = placement.text().await.unwrap();
+    /// Cancel an [`Order`] with an EIP-712 signature of the `signer`.
+    /// If the response status is not `200`, return a [`ServicesError`].
+    pub async fn cancel_order(
+        &self,
+        uid: &OrderUid,
+        signer: &TestAccount,
+    ) -> Result<(), ServicesError> {
+        let cancellation = OrderCancellation::for_order(
+            *uid,
+            &self.contracts.domain_separator,
+            SecretKeyRef::from(&SecretKey::from_slice(signer.private_key()).unwrap()),
+        );
+        let request = self
+            .http
+            .delete(format!("{API_HOST}{ORDERS_ENDPOINT}/{uid}"))
+            .json(&CancellationPayload {
+                signature: cancellation.signature,
+                signing_scheme: cancellation.signing_scheme,
+            });
+        Self::send(request, &[StatusCode::OK]).await.map(|_| ())
+    }
 
-        match status {
-            StatusCode::CREATED => Ok(serde_json::from_str(&body).unwrap()),
-            code => Err((code, body)),
-        }
+    /// Cancel multiple [`Order`]s at once with a single EIP-712 signature of
+    /// the `signer`.
+    /// If the response status is not `200`, return a [`ServicesError`].
+    pub async fn cancel_orders(
+        &self,
+        uids: Vec<OrderUid>,
+        signer: &TestAccount,
+    ) -> Result<(), ServicesError> {
+        let cancellations = OrderCancellations { order_uids: uids };
+        let signature = signer.sign_typed_data(
+            &self.contracts.domain_separator,
+            &cancellations.hash_struct(),
+        );
+        let request = self
+            .http
+            .delete(format!("{API_HOST}{ORDERS_ENDPOINT}"))
+            .json(&SignedOrderCancellations {
+                data: cancellations,
+                signature,
+                signing_scheme: EcdsaSigningScheme::Eip712,
+            });
+        Self::send(request, &[StatusCode::OK]).await.map(|_| ())
     }
 
     /// Submit an [`model::quote::OrderQuote`].
-    /// If the response status is not `200`, return the status and the body.
+    /// If the response status is not `200`, return a [`ServicesError`].
     pub async fn submit_quote(
         &self,
         quote: &OrderQuoteRequest,
-    ) -> Result<OrderQuoteResponse, (StatusCode, String)> {
-        let quoting = self
-            .http
-            .post(&format!("{API_HOST}{QUOTING_ENDPOINT}"))
-            .json(&quote)
-            .send()
+    ) -> Result<OrderQuoteResponse, ServicesError> {
+        self.post_json(QUOTING_ENDPOINT, quote, StatusCode::OK)
             .await
-            .unwrap();
-
-        let status = quoting.status();
-        let body = quoting.text().await.unwrap();
-
-        match status {
-            StatusCode::OK => Ok(serde_json::from_str(&body).unwrap()),
-            code => Err((code, body)),
-        }
     }
 
     pub async fn solvable_orders(&self) -> usize {
         self.get_auction().await.auction.orders.len()
     }
 
-    /// Retrieve an [`Order`]. If the respons status is not `200`, return the
-    /// status and the body.
-    pub async fn get_order(&self, uid: &OrderUid) -> Result<Order, (StatusCode, String)> {
-        let response = self
-            .http
-            .get(format!("{API_HOST}{ORDERS_ENDPOINT}/{uid}"))
-            .send()
+    /// Retrieve an [`Order`]. If the respons status is not `200`, return a
+    /// [`ServicesError`].
+    pub async fn get_order(&self, uid: &OrderUid) -> Result<Order, ServicesError> {
+        self.get_json(&format!("{ORDERS_ENDPOINT}/{uid}")).await
+    }
+
+    /// Repeatedly retrieves the [`Order`] until it reaches the given status.
+    /// Returns an error if the order does not reach that status before the
+    /// timeout.
+    pub async fn wait_for_order_status(
+        &self,
+        uid: &OrderUid,
+        status: OrderStatus,
+        timeout: Duration,
+    ) -> Result<Order> {
+        let has_status = || async {
+            matches!(
+                self.get_order(uid).await,
+                Ok(order) if order.metadata.status == status
+            )
+        };
+        wait_for_condition(timeout, has_status)
             .await
-            .unwrap();
+            .with_context(|| format!("order {uid} did not reach status {status:?}"))?;
 
-        let status = response.status();
-        let body = response.text().await.unwrap();
+        self.get_order(uid)
+            .await
+            .map_err(|err| anyhow!("failed to fetch order {uid}: {err:?}"))
+    }
 
-        match status {
-            StatusCode::OK => Ok(serde_json::from_str(&body).unwrap()),
-            code => Err((code, body)),
-        }
+    /// Returns the labels of all events of that order in the order they
+    /// happened (old to new).
+    pub async fn get_order_events(&self, uid: &OrderUid) -> Vec<OrderEventLabel> {
+        const QUERY: &str =
+            "SELECT label FROM order_events WHERE order_uid = $1 ORDER BY timestamp ASC";
+        sqlx::query_scalar(QUERY)
+            .bind(ByteArray(uid.0))
+            .fetch_all(&self.db)
+            .await
+            .unwrap()
     }
 
     pub async fn get_app_data_document(
         &self,
         app_data: AppDataHash,
-    ) -> Result<AppDataDocument, (StatusCode, String)> {
-        let response = self
-            .http
-            .get(format!("{API_HOST}/api/v1/app_data/{app_data:?}"))
-            .send()
+    ) -> Result<AppDataDocument, ServicesError> {
+        self.get_json(&format!("/api/v1/app_data/{app_data:?}"))
             .await
-            .unwrap();
-
-        let status = response.status();
-        let body = response.text().await.unwrap();
-
-        match status {
-            StatusCode::OK => Ok(serde_json::from_str(&body).unwrap()),
-            code => Err((code, body)),
-        }
     }
 
-    pub async fn get_app_data(
-        &self,
-        app_data: AppDataHash,
-    ) -> Result<String, (StatusCode, String)> {
+    pub async fn get_app_data(&self, app_data: AppDataHash) -> Result<String, ServicesError> {
         Ok(self.get_app_data_document(app_data).await?.full_app_data)
     }
 
@@ -335,30 +544,22 @@ impl<'a> Services<'a> {
         &self,
         app_data: AppDataHash,
         document: AppDataDocument,
-    ) -> Result<(), (StatusCode, String)> {
-        let response = self
+    ) -> Result<(), ServicesError> {
+        let request = self
             .http
             .put(format!("{API_HOST}/api/v1/app_data/{app_data:?}"))
-            .json(&document)
-            .send()
+            .json(&document);
+        // `201` if the document is new and `200` if it already existed.
+        Self::send(request, &[StatusCode::CREATED, StatusCode::OK])
             .await
-            .unwrap();
-
-        let status = response.status();
-        let body = response.text().await.unwrap();
-
-        if status.is_success() {
-            Ok(())
-        } else {
-            Err((status, body))
-        }
+            .map(|_| ())
     }
 
     pub async fn put_app_data(
         &self,
         app_data: AppDataHash,
         full_app_data: &str,
-    ) -> Result<(), (StatusCode, String)> {
+    ) -> Result<(), ServicesError> {
         self.put_app_data_document(
             app_data,
             AppDataDocument {
@@ -379,6 +580,170 @@ impl<'a> Services<'a> {
     }
 }
 
+/// An autopilot running on its own runtime. Shutting down the runtime also
+/// kills every task the autopilot spawned, which aborting the future returned
+/// by [`autopilot::run`] alone would not do. The autopilot gets shut down
+/// when the handle is dropped.
+struct AutopilotHandle {
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AutopilotHandle {
+    fn spawn(args: autopilot::arguments::Arguments) -> Self {
+        let (shutdown, shutdown_received) = oneshot::channel();
+        let thread = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                tokio::select! {
+                    _ = autopilot::run(args) => {},
+                    _ = shutdown_received => {},
+                }
+            });
+            runtime.shutdown_timeout(Duration::from_secs(5));
+        });
+        Self {
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        }
+    }
+
+    /// Blocks until the autopilot and all of its tasks are shut down.
+    fn shutdown(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            // The autopilot might already have exited on its own.
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() && !std::thread::panicking() {
+                panic!("autopilot panicked");
+            }
+        }
+    }
+}
+
+impl Drop for AutopilotHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Price estimators the API and the autopilot get started with. Defaults to
+/// Baseline for both.
+#[derive(Clone, Debug)]
+pub struct PriceEstimators {
+    /// Value of the `--price-estimators` argument.
+    pub price_estimators: String,
+    /// Value of the `--native-price-estimators` argument.
+    pub native_price_estimators: String,
+}
+
+impl Default for PriceEstimators {
+    fn default() -> Self {
+        Self {
+            price_estimators: "Baseline|0x0000000000000000000000000000000000000001".to_string(),
+            native_price_estimators: "Baseline".to_string(),
+        }
+    }
+}
+
+/// Unsuccessful response of the API.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServicesError {
+    /// The API responded with `404`.
+    NotFound { body: String },
+    /// The API responded with `400`.
+    BadRequest { body: String },
+    /// The API responded with any other unsuccessful status.
+    Unexpected { status: StatusCode, body: String },
+}
+
+impl ServicesError {
+    fn new(status: StatusCode, body: String) -> Self {
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound { body },
+            StatusCode::BAD_REQUEST => Self::BadRequest { body },
+            status => Self::Unexpected { status, body },
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::NotFound { .. } => StatusCode::NOT_FOUND,
+            Self::BadRequest { .. } => StatusCode::BAD_REQUEST,
+            Self::Unexpected { status, .. } => *status,
+        }
+    }
+
+    /// The response body.
+    pub fn body(&self) -> &str {
+        match self {
+            Self::NotFound { body } | Self::BadRequest { body } | Self::Unexpected { body, .. } => {
+                body
+            }
+        }
+    }
+}
+
+impl From<ServicesError> for (StatusCode, String) {
+    fn from(err: ServicesError) -> Self {
+        (err.status(), err.body().to_owned())
+    }
+}
+
+/// Account a solver service settles auctions with.
+pub enum SolverAccount {
+    /// Transactions get signed locally with this key.
+    PrivateKey([u8; 32]),
+    /// Transactions get signed by the node which has to have this account
+    /// unlocked.
+    Address(H160),
+}
+
+impl SolverAccount {
+    pub fn address(&self) -> H160 {
+        match self {
+            Self::PrivateKey(key) => PrivateKey::from_raw(*key).unwrap().public_address(),
+            Self::Address(address) => *address,
+        }
+    }
+
+    fn to_arg(&self) -> String {
+        match self {
+            Self::PrivateKey(key) => hex::encode(key),
+            Self::Address(address) => format!("{address:#x}"),
+        }
+    }
+}
+
+/// Configuration of a single solver service started with
+/// [`Services::start_drivers`].
+pub struct DriverConfig {
+    pub account: SolverAccount,
+    /// URL of a custom external http solver. If it is not set the solver
+    /// service uses its default solvers.
+    pub solver_url: Option<Url>,
+    /// Port the solver service serves its metrics on.
+    pub metrics_port: u16,
+    pub extra_args: Vec<String>,
+}
+
+impl DriverConfig {
+    /// The default metrics port of the solver service.
+    pub const DEFAULT_METRICS_PORT: u16 = 9587;
+}
+
+/// A running solver service.
+pub struct DriverHandle {
+    /// Address of the solver account which allows tests to check which solver
+    /// won an auction.
+    pub solver: H160,
+    pub task: tokio::task::JoinHandle<()>,
+}
+
 pub async fn clear_database() {
     tracing::info!("Clearing database.");
     let mut db = sqlx::PgConnection::connect(LOCAL_DB_URL).await.unwrap();
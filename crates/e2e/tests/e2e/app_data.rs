@@ -6,7 +6,6 @@ use {
         order::{OrderCreation, OrderCreationAppData, OrderKind},
         signature::EcdsaSigningScheme,
     },
-    reqwest::StatusCode,
     secp256k1::SecretKey,
     shared::ethrpc::Web3,
     web3::signing::SecretKeyRef,
@@ -71,7 +70,7 @@ async fn app_data(web3: Web3) {
         .get_app_data(AppDataHash([1; 32]))
         .await
         .unwrap_err();
-    assert_eq!(err.0, StatusCode::NOT_FOUND);
+    assert!(matches!(err, ServicesError::NotFound { .. }));
 
     // hash matches
     let app_data = "{}";
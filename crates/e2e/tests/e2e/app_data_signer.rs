@@ -9,7 +9,6 @@ use {
         order::{OrderCreation, OrderCreationAppData, OrderKind},
         signature::EcdsaSigningScheme,
     },
-    reqwest::StatusCode,
     secp256k1::SecretKey,
     shared::ethrpc::Web3,
     web3::signing::SecretKeyRef,
@@ -77,7 +76,7 @@ async fn order_creation_checks_metadata_signer(web3: Web3) {
     );
     assert!(matches!(
         services.get_app_data(custom_hash_app_data).await,
-        Err((StatusCode::NOT_FOUND, ..))
+        Err(ServicesError::NotFound { .. })
     ));
     let uid = services.create_order(&order0).await.unwrap();
     assert!(matches!(services.get_order(&uid).await, Ok(..)));
@@ -86,7 +85,7 @@ async fn order_creation_checks_metadata_signer(web3: Web3) {
     let full_app_data = full_app_data_with_signer(adversary.address());
     let order1 = sign(create_order(full_app_data), &trader);
     let err = services.create_order(&order1).await.unwrap_err();
-    assert!(dbg!(err).1.contains("WrongOwner"));
+    assert!(dbg!(err).body().contains("WrongOwner"));
 
     // Accepted: app data with correct signer.
     let full_app_data = full_app_data_with_signer(trader.address());
@@ -104,7 +103,7 @@ async fn order_creation_checks_metadata_signer(web3: Web3) {
         &adversary,
     );
     let err = services.create_order(&order3).await.unwrap_err();
-    assert!(dbg!(err).1.contains("WrongOwner"));
+    assert!(dbg!(err).body().contains("WrongOwner"));
 
     // EIP-1271
 
@@ -125,7 +124,7 @@ async fn order_creation_checks_metadata_signer(web3: Web3) {
     order5.from = Some(safe.address());
     safe.sign_order(&mut order5, &onchain);
     let err = services.create_order(&order5).await.unwrap_err();
-    assert!(err.1.contains("AppdataFromMismatch"));
+    assert!(err.body().contains("AppdataFromMismatch"));
 }
 
 fn full_app_data_with_signer(signer: H160) -> OrderCreationAppData {
@@ -0,0 +1,141 @@
+use {
+    e2e::{setup::*, tx},
+    ethcontract::prelude::U256,
+    model::{
+        order::{OrderCreation, OrderKind},
+        signature::EcdsaSigningScheme,
+    },
+    secp256k1::SecretKey,
+    shared::ethrpc::Web3,
+    std::ops::DerefMut,
+    web3::signing::SecretKeyRef,
+};
+
+#[tokio::test]
+#[ignore]
+async fn local_node_autopilot_restart() {
+    run_test(autopilot_restart).await;
+}
+
+async fn autopilot_restart(web3: Web3) {
+    let mut onchain = OnchainComponents::deploy(web3).await;
+
+    let [solver] = onchain.make_solvers(to_wei(1)).await;
+    let [trader] = onchain.make_accounts(to_wei(1)).await;
+    let [token_a, token_b] = onchain
+        .deploy_tokens_with_weth_uni_v2_pools(to_wei(1_000), to_wei(1_000))
+        .await;
+
+    // Fund trader account
+    token_a.mint(trader.address(), to_wei(10)).await;
+
+    // Create and fund Uniswap pool
+    token_a.mint(solver.address(), to_wei(1000)).await;
+    token_b.mint(solver.address(), to_wei(1000)).await;
+    tx!(
+        solver.account(),
+        onchain
+            .contracts()
+            .uniswap_v2_factory
+            .create_pair(token_a.address(), token_b.address())
+    );
+    tx!(
+        solver.account(),
+        token_a.approve(
+            onchain.contracts().uniswap_v2_router.address(),
+            to_wei(1000)
+        )
+    );
+    tx!(
+        solver.account(),
+        token_b.approve(
+            onchain.contracts().uniswap_v2_router.address(),
+            to_wei(1000)
+        )
+    );
+    tx!(
+        solver.account(),
+        onchain.contracts().uniswap_v2_router.add_liquidity(
+            token_a.address(),
+            token_b.address(),
+            to_wei(1000),
+            to_wei(1000),
+            0_u64.into(),
+            0_u64.into(),
+            solver.address(),
+            U256::max_value(),
+        )
+    );
+
+    // Approve GPv2 for trading
+    tx!(
+        trader.account(),
+        token_a.approve(onchain.contracts().allowance, to_wei(10))
+    );
+
+    let services = Services::new(onchain.contracts()).await;
+    services.start_autopilot(vec![]);
+    services.start_api(vec![]).await;
+
+    let order = OrderCreation {
+        sell_token: token_a.address(),
+        sell_amount: to_wei(10),
+        buy_token: token_b.address(),
+        buy_amount: to_wei(5),
+        valid_to: model::time::now_in_epoch_seconds() + 300,
+        kind: OrderKind::Sell,
+        ..Default::default()
+    }
+    .sign(
+        EcdsaSigningScheme::Eip712,
+        &onchain.contracts().domain_separator,
+        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
+    );
+    let uid = services.create_order(&order).await.unwrap();
+    wait_for_condition(TIMEOUT, || async { services.solvable_orders().await == 1 })
+        .await
+        .unwrap();
+
+    tracing::info!("Restarting autopilot before the order gets settled.");
+    services.restart_autopilot(vec![]).await;
+    wait_for_condition(TIMEOUT, || async { services.solvable_orders().await == 1 })
+        .await
+        .unwrap();
+
+    tracing::info!("Waiting for trade.");
+    services.start_old_driver(solver.private_key(), vec![]);
+    wait_for_condition(TIMEOUT, || async { services.solvable_orders().await == 0 })
+        .await
+        .unwrap();
+
+    tracing::info!("Restarting autopilot before the settlement gets indexed.");
+    services.restart_autopilot(vec![]).await;
+    onchain.mint_blocks_past_reorg_threshold().await;
+    let settlement_indexed = || async {
+        onchain.mint_block().await;
+        count_rows(services.db(), "settlement_observations").await == 1
+    };
+    wait_for_condition(TIMEOUT, settlement_indexed)
+        .await
+        .unwrap();
+
+    tracing::info!("Restarting autopilot after the settlement got indexed.");
+    services.restart_autopilot(vec![]).await;
+    onchain.mint_blocks_past_reorg_threshold().await;
+    services.mine_block_and_wait_for_auction(&onchain).await;
+
+    assert_eq!(services.get_trades(&uid).await.unwrap().len(), 1);
+    assert_eq!(count_rows(services.db(), "settlements").await, 1);
+    assert_eq!(
+        count_rows(services.db(), "settlement_observations").await,
+        1
+    );
+}
+
+async fn count_rows(db: &Db, table: &str) -> i64 {
+    let mut db = db.acquire().await.unwrap();
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+        .fetch_one(db.deref_mut())
+        .await
+        .unwrap()
+}
@@ -117,7 +117,7 @@ async fn test(web3: Web3) {
         .await
         .unwrap();
 
-    let tx_hash = services.get_trades(&uid).await.unwrap()[0].tx_hash.unwrap();
+    let tx_hash = services.wait_for_settlement(&uid, TIMEOUT).await.unwrap();
     let competition = services.get_solver_competition(tx_hash).await.unwrap();
     assert!(!competition.common.solutions.is_empty());
     assert!(competition.common.auction.orders.contains(&uid));
@@ -461,9 +461,11 @@ async fn too_many_limit_orders_test(web3: Web3) {
         &onchain.contracts().domain_separator,
         SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
     );
-    let (status, body) = services.create_order(&order).await.unwrap_err();
-    assert_eq!(status, 400);
-    assert!(body.contains("TooManyLimitOrders"));
+    let err = services.create_order(&order).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ServicesError::BadRequest { body } if body.contains("TooManyLimitOrders")
+    ));
 }
 
 async fn forked_single_limit_order_test(web3: Web3) {
@@ -6,6 +6,7 @@
 // Each of the following modules contains tests.
 mod app_data;
 mod app_data_signer;
+mod autopilot_restart;
 mod colocation_ethflow;
 mod colocation_hooks;
 mod colocation_partial_fill;
@@ -16,6 +17,7 @@ mod eth_integration;
 mod ethflow;
 mod hooks;
 mod limit_orders;
+mod multiple_drivers;
 mod onchain_settlement;
 mod order_cancellation;
 mod partially_fillable_balance;
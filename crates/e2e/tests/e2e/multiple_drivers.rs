@@ -0,0 +1,124 @@
+use {
+    e2e::{setup::*, tx},
+    ethcontract::prelude::U256,
+    model::{
+        order::{OrderCreation, OrderKind},
+        signature::EcdsaSigningScheme,
+    },
+    secp256k1::SecretKey,
+    shared::ethrpc::Web3,
+    web3::signing::SecretKeyRef,
+};
+
+#[tokio::test]
+#[ignore]
+async fn local_node_multiple_drivers() {
+    run_test(multiple_drivers).await;
+}
+
+async fn multiple_drivers(web3: Web3) {
+    let mut onchain = OnchainComponents::deploy(web3).await;
+
+    let [solver_a, solver_b] = onchain.make_solvers(to_wei(1)).await;
+    let [trader] = onchain.make_accounts(to_wei(1)).await;
+    let [token_a, token_b] = onchain
+        .deploy_tokens_with_weth_uni_v2_pools(to_wei(1_000), to_wei(1_000))
+        .await;
+
+    // Fund trader account
+    token_a.mint(trader.address(), to_wei(10)).await;
+
+    // Create and fund Uniswap pool
+    token_a.mint(solver_a.address(), to_wei(1000)).await;
+    token_b.mint(solver_a.address(), to_wei(1000)).await;
+    tx!(
+        solver_a.account(),
+        onchain
+            .contracts()
+            .uniswap_v2_factory
+            .create_pair(token_a.address(), token_b.address())
+    );
+    tx!(
+        solver_a.account(),
+        token_a.approve(
+            onchain.contracts().uniswap_v2_router.address(),
+            to_wei(1000)
+        )
+    );
+    tx!(
+        solver_a.account(),
+        token_b.approve(
+            onchain.contracts().uniswap_v2_router.address(),
+            to_wei(1000)
+        )
+    );
+    tx!(
+        solver_a.account(),
+        onchain.contracts().uniswap_v2_router.add_liquidity(
+            token_a.address(),
+            token_b.address(),
+            to_wei(1000),
+            to_wei(1000),
+            0_u64.into(),
+            0_u64.into(),
+            solver_a.address(),
+            U256::max_value(),
+        )
+    );
+
+    // Approve GPv2 for trading
+    tx!(
+        trader.account(),
+        token_a.approve(onchain.contracts().allowance, to_wei(10))
+    );
+
+    let services = Services::new(onchain.contracts()).await;
+    services.start_autopilot(vec![]);
+    services.start_api(vec![]).await;
+
+    let order = OrderCreation {
+        sell_token: token_a.address(),
+        sell_amount: to_wei(10),
+        buy_token: token_b.address(),
+        buy_amount: to_wei(5),
+        valid_to: model::time::now_in_epoch_seconds() + 300,
+        kind: OrderKind::Sell,
+        ..Default::default()
+    }
+    .sign(
+        EcdsaSigningScheme::Eip712,
+        &onchain.contracts().domain_separator,
+        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
+    );
+    services.create_order(&order).await.unwrap();
+    wait_for_condition(TIMEOUT, || async { services.solvable_orders().await == 1 })
+        .await
+        .unwrap();
+
+    tracing::info!("Starting two solver services.");
+    let driver = |solver: &TestAccount, metrics_port| DriverConfig {
+        account: SolverAccount::PrivateKey(*solver.private_key()),
+        solver_url: None,
+        metrics_port,
+        extra_args: vec![],
+    };
+    let drivers = services.start_drivers(vec![driver(&solver_a, 9600), driver(&solver_b, 9601)]);
+    assert_eq!(drivers[0].solver, solver_a.address());
+    assert_eq!(drivers[1].solver, solver_b.address());
+
+    // Every solver service serves its own metrics.
+    for port in [9600, 9601] {
+        let serves_metrics = || async {
+            reqwest::get(format!("http://localhost:{port}/metrics"))
+                .await
+                .is_ok_and(|response| response.status().is_success())
+        };
+        wait_for_condition(TIMEOUT, serves_metrics).await.unwrap();
+    }
+
+    tracing::info!("Waiting for trade.");
+    wait_for_condition(TIMEOUT, || async { services.solvable_orders().await == 0 })
+        .await
+        .unwrap();
+    assert!(drivers.iter().all(|handle| !handle.task.is_finished()));
+}
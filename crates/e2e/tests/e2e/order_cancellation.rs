@@ -4,17 +4,9 @@ use {
     ethcontract::prelude::U256,
     model::{
         app_data::AppDataHash,
-        order::{
-            CancellationPayload,
-            OrderCancellation,
-            OrderCancellations,
-            OrderCreation,
-            OrderStatus,
-            OrderUid,
-            SignedOrderCancellations,
-        },
+        order::{OrderCreation, OrderStatus},
         quote::{OrderQuoteRequest, OrderQuoteSide, SellAmount},
-        signature::{EcdsaSignature, EcdsaSigningScheme},
+        signature::EcdsaSigningScheme,
     },
     number::nonzero::U256 as NonZeroU256,
     secp256k1::SecretKey,
@@ -89,58 +81,6 @@ async fn order_cancellation(web3: Web3) {
         }
     };
 
-    let cancel_order = |order_uid: OrderUid| {
-        let client = services.client();
-        let cancellation = OrderCancellation::for_order(
-            order_uid,
-            &onchain.contracts().domain_separator,
-            SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-        );
-
-        async move {
-            let cancellation = client
-                .delete(&format!("{API_HOST}{ORDERS_ENDPOINT}/{order_uid}"))
-                .json(&CancellationPayload {
-                    signature: cancellation.signature,
-                    signing_scheme: cancellation.signing_scheme,
-                })
-                .send()
-                .await
-                .unwrap();
-
-            assert_eq!(cancellation.status(), 200);
-        }
-    };
-
-    let cancel_orders = |order_uids: Vec<OrderUid>| {
-        let client = services.client();
-        let cancellations = OrderCancellations { order_uids };
-        let signing_scheme = EcdsaSigningScheme::Eip712;
-        let signature = EcdsaSignature::sign(
-            EcdsaSigningScheme::Eip712,
-            &onchain.contracts().domain_separator,
-            &cancellations.hash_struct(),
-            SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-        );
-
-        let signed_cancellations = SignedOrderCancellations {
-            data: cancellations,
-            signature,
-            signing_scheme,
-        };
-
-        async move {
-            let cancellation = client
-                .delete(&format!("{API_HOST}{ORDERS_ENDPOINT}"))
-                .json(&signed_cancellations)
-                .send()
-                .await
-                .unwrap();
-
-            assert_eq!(cancellation.status(), 200);
-        }
-    };
-
     // Place 3 orders.
     let order_uids = vec![
         place_order(0).await,
@@ -164,7 +104,10 @@ async fn order_cancellation(web3: Web3) {
     }
 
     // Cancel one of them.
-    cancel_order(order_uids[0]).await;
+    services
+        .cancel_order(&order_uids[0], &trader)
+        .await
+        .unwrap();
     wait_for_condition(TIMEOUT, || async {
         services.get_auction().await.auction.orders.len() == 2
     })
@@ -181,7 +124,10 @@ async fn order_cancellation(web3: Web3) {
     );
 
     // Cancel the other two.
-    cancel_orders(vec![order_uids[1], order_uids[2]]).await;
+    services
+        .cancel_orders(vec![order_uids[1], order_uids[2]], &trader)
+        .await
+        .unwrap();
     wait_for_condition(TIMEOUT, || async {
         services.get_auction().await.auction.orders.is_empty()
     })
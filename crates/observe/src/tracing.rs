@@ -2,14 +2,24 @@ use {
     std::{panic::PanicInfo, sync::Once},
     time::macros::format_description,
     tracing::level_filters::LevelFilter,
-    tracing_subscriber::fmt::{time::UtcTime, writer::MakeWriterExt as _},
+    tracing_subscriber::fmt::{
+        time::UtcTime,
+        writer::{BoxMakeWriter, MakeWriterExt as _},
+    },
 };
 
 /// Initializes tracing setup that is shared between the binaries.
 /// `env_filter` has similar syntax to env_logger. It is documented at
 /// https://docs.rs/tracing-subscriber/0.2.15/tracing_subscriber/filter/struct.EnvFilter.html
 pub fn initialize(env_filter: &str, stderr_threshold: LevelFilter) {
-    set_tracing_subscriber(env_filter, stderr_threshold);
+    set_tracing_subscriber(env_filter, stderr_threshold, false);
+    std::panic::set_hook(Box::new(tracing_panic_hook));
+}
+
+/// Like [`initialize`], but every log line is a JSON object which contains the
+/// fields of the event as separate keys.
+pub fn initialize_json(env_filter: &str, stderr_threshold: LevelFilter) {
+    set_tracing_subscriber(env_filter, stderr_threshold, true);
     std::panic::set_hook(Box::new(tracing_panic_hook));
 }
 
@@ -21,26 +31,30 @@ pub fn initialize_reentrant(env_filter: &str) {
     // The tracing subscriber below is global object so initializing it again in the
     // same process by a different thread would fail.
     static ONCE: Once = Once::new();
-    ONCE.call_once(|| set_tracing_subscriber(env_filter, LevelFilter::ERROR));
+    ONCE.call_once(|| set_tracing_subscriber(env_filter, LevelFilter::ERROR, false));
 }
 
-fn set_tracing_subscriber(env_filter: &str, stderr_threshold: LevelFilter) {
+fn set_tracing_subscriber(env_filter: &str, stderr_threshold: LevelFilter, json: bool) {
+    let writer = match stderr_threshold.into_level() {
+        Some(threshold) => BoxMakeWriter::new(
+            std::io::stderr
+                .with_max_level(threshold)
+                .or_else(std::io::stdout),
+        ),
+        None => BoxMakeWriter::new(std::io::stdout),
+    };
     // This is what kibana uses to separate multi line log messages.
     let subscriber_builder = tracing_subscriber::fmt::fmt()
         .with_timer(UtcTime::new(format_description!(
             "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
         )))
         .with_env_filter(env_filter)
-        .with_ansi(atty::is(atty::Stream::Stdout));
-    match stderr_threshold.into_level() {
-        Some(threshold) => subscriber_builder
-            .with_writer(
-                std::io::stderr
-                    .with_max_level(threshold)
-                    .or_else(std::io::stdout),
-            )
+        .with_writer(writer);
+    match json {
+        true => subscriber_builder.json().flatten_event(true).init(),
+        false => subscriber_builder
+            .with_ansi(atty::is(atty::Stream::Stdout))
             .init(),
-        None => subscriber_builder.init(),
     }
 }
 
@@ -80,6 +80,11 @@ pub struct Arguments {
     )]
     pub token_quality_cache_expiry: Duration,
 
+    /// How many rounds of transfers into and out of the settlement contract
+    /// get simulated to detect tokens that only misbehave on later transfers.
+    #[clap(long, env, default_value = "1")]
+    pub token_quality_transfer_rounds: usize,
+
     /// List of token addresses to be ignored throughout service
     #[clap(long, env, use_value_delimiter = true)]
     pub unsupported_tokens: Vec<H160>,
@@ -203,6 +208,11 @@ impl std::fmt::Display for Arguments {
             "token_quality_cache_expiry: {:?}",
             self.token_quality_cache_expiry
         )?;
+        writeln!(
+            f,
+            "token_quality_transfer_rounds: {}",
+            self.token_quality_transfer_rounds
+        )?;
         writeln!(f, "unsupported_tokens: {:?}", self.unsupported_tokens)?;
         writeln!(f, "banned_users: {:?}", self.banned_users)?;
         writeln!(f, "allowed_tokens: {:?}", self.allowed_tokens)?;
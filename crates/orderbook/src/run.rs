@@ -216,6 +216,11 @@ pub async fn run(args: Arguments) {
         other => Some(other.unwrap()),
     };
 
+    // The token owner finders share this list. It gets updated at runtime if
+    // a base tokens file is configured.
+    let owner_finder_base_tokens = token_owner_finder::liquidity::BaseTokenList::new(
+        base_tokens.tokens().iter().copied().collect(),
+    );
     let finder = token_owner_finder::init(
         &args.token_owner_finder,
         web3.clone(),
@@ -224,7 +229,7 @@ pub async fn run(args: Arguments) {
         &pair_providers,
         vault.as_ref(),
         uniswapv3_factory.as_ref(),
-        &base_tokens,
+        &owner_finder_base_tokens,
     )
     .await
     .expect("failed to initialize token owner finders");
@@ -240,6 +245,7 @@ pub async fn run(args: Arguments) {
                 ),
                 finder,
                 settlement_contract: settlement_contract.address(),
+                transfer_rounds: args.token_quality_transfer_rounds,
             }),
             args.token_quality_cache_expiry,
         ))
@@ -270,6 +276,7 @@ pub async fn run(args: Arguments) {
         maximum_recent_block_age: args.shared.pool_cache_maximum_recent_block_age,
         max_retries: args.shared.pool_cache_maximum_retries,
         delay_between_retries: args.shared.pool_cache_delay_between_retries_seconds,
+        negative_ttl: None,
     };
     let pool_fetcher = Arc::new(
         PoolCache::new(
@@ -9,6 +9,11 @@ use {
     },
 };
 
+/// How long an unknown token quality gets cached. It only reflects a
+/// transient failure so it is kept much shorter than actual verdicts, but
+/// long enough to not probe the node again for every request.
+const UNKNOWN_QUALITY_CACHE_EXPIRY: Duration = Duration::from_secs(10);
+
 pub struct CachingDetector {
     inner: Box<dyn BadTokenDetecting>,
     // std mutex is fine because we don't hold lock across await.
@@ -39,14 +44,15 @@ impl CachingDetector {
     }
 
     fn get_from_cache(&self, token: &H160, now: Instant) -> Option<TokenQuality> {
-        match self.cache.lock().unwrap().get(token) {
-            Some((instant, quality))
-                if now.checked_duration_since(*instant).unwrap_or_default() < self.cache_expiry =>
-            {
-                Some(quality.clone())
-            }
-            _ => None,
-        }
+        let cache = self.cache.lock().unwrap();
+        let (instant, quality) = cache.get(token)?;
+        // An unknown quality is not a verdict about the token so don't keep it
+        // around for the full cache expiry.
+        let expiry = match quality {
+            TokenQuality::Unknown { .. } => self.cache_expiry.min(UNKNOWN_QUALITY_CACHE_EXPIRY),
+            TokenQuality::Good | TokenQuality::Bad { .. } => self.cache_expiry,
+        };
+        (now.checked_duration_since(*instant).unwrap_or_default() < expiry).then(|| quality.clone())
     }
 
     fn insert_into_cache(&self, token: H160, quality: TokenQuality) {
@@ -99,4 +105,20 @@ mod tests {
             .get_from_cache(&token, now + Duration::from_secs(3))
             .is_none());
     }
+
+    #[test]
+    fn unknown_quality_expires_sooner() {
+        let inner = MockBadTokenDetecting::new();
+        let token = H160::from_low_u64_le(0);
+        let detector = CachingDetector::new(Box::new(inner), Duration::from_secs(600));
+        let now = Instant::now();
+        let unknown = TokenQuality::unknown("node error");
+        detector.cache.lock().unwrap().insert(token, (now, unknown));
+        assert!(detector
+            .get_from_cache(&token, now + Duration::from_secs(1))
+            .is_some());
+        assert!(detector
+            .get_from_cache(&token, now + UNKNOWN_QUALITY_CACHE_EXPIRY)
+            .is_none());
+    }
 }
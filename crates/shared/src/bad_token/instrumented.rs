@@ -20,8 +20,8 @@ impl<T: BadTokenDetecting + 'static> InstrumentedBadTokenDetectorExt for T {
 #[derive(MetricStorage, Clone, Debug)]
 #[metric(subsystem = "token_quality")]
 struct Metrics {
-    /// Tracks how many token detections result in good or bad token quality or
-    /// an error.
+    /// Tracks how many token detections result in good, bad or unknown token
+    /// quality or an error.
     #[metric(labels("quality"))]
     results: IntCounterVec,
 }
@@ -51,6 +51,10 @@ impl BadTokenDetecting for InstrumentedBadTokenDetector {
                 tracing::warn!("bad token detection for {:?} returned {:?}", token, quality);
                 "bad"
             }
+            Ok(quality @ TokenQuality::Unknown { .. }) => {
+                tracing::warn!("bad token detection for {:?} returned {:?}", token, quality);
+                "unknown"
+            }
         };
 
         Metrics::instance(observe::metrics::get_storage_registry())
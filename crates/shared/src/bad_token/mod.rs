@@ -11,6 +11,12 @@ use {anyhow::Result, primitive_types::H160};
 pub enum TokenQuality {
     Good,
     Bad { reason: String },
+    /// The quality could not be determined because the node responses could
+    /// not be interpreted. This is not a verdict about the token and should
+    /// only be cached briefly, if at all.
+    Unknown {
+        reason: String,
+    },
 }
 
 impl TokenQuality {
@@ -18,11 +24,29 @@ impl TokenQuality {
         matches!(self, Self::Good { .. })
     }
 
+    pub fn is_bad(&self) -> bool {
+        matches!(self, Self::Bad { .. })
+    }
+
+    /// The reason the token was classified as bad, if it was.
+    pub fn bad_reason(&self) -> Option<&str> {
+        match self {
+            Self::Bad { reason } => Some(reason),
+            Self::Good | Self::Unknown { .. } => None,
+        }
+    }
+
     pub fn bad(reason: impl ToString) -> Self {
         Self::Bad {
             reason: reason.to_string(),
         }
     }
+
+    pub fn unknown(reason: impl ToString) -> Self {
+        Self::Unknown {
+            reason: reason.to_string(),
+        }
+    }
 }
 
 /// Detect how well behaved a token is.
@@ -31,3 +55,26 @@ impl TokenQuality {
 pub trait BadTokenDetecting: Send + Sync {
     async fn detect(&self, token: H160) -> Result<TokenQuality>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_quality_accessors() {
+        let good = TokenQuality::Good;
+        assert!(good.is_good());
+        assert!(!good.is_bad());
+        assert_eq!(good.bad_reason(), None);
+
+        let bad = TokenQuality::bad("no pool");
+        assert!(!bad.is_good());
+        assert!(bad.is_bad());
+        assert_eq!(bad.bad_reason(), Some("no pool"));
+
+        let unknown = TokenQuality::unknown("node error");
+        assert!(!unknown.is_good());
+        assert!(!unknown.is_bad());
+        assert_eq!(unknown.bad_reason(), None);
+    }
+}
@@ -9,6 +9,7 @@ use {
         blockscout::BlockscoutTokenOwnerFinder,
         liquidity::{
             BalancerVaultFinder,
+            BaseTokenList,
             FeeValues,
             UniswapLikePairProviderFinder,
             UniswapV3Finder,
@@ -24,7 +25,6 @@ use {
             },
             token_owner_list::TokenOwnerList,
         },
-        baseline_solver::BaseTokens,
         ethcontract_error::EthcontractErrorType,
         ethrpc::{Web3, Web3CallBatch, MAX_BATCH_SIZE},
         http_client::HttpClientFactory,
@@ -40,6 +40,7 @@ use {
     std::{
         collections::HashMap,
         fmt::{self, Display, Formatter},
+        path::PathBuf,
         sync::Arc,
         time::Duration,
     },
@@ -111,6 +112,16 @@ pub struct Arguments {
     /// `solver_token_owners_urls`
     #[clap(long, env, use_value_delimiter = true, value_parser = duration_from_seconds)]
     pub solver_token_owners_cache_update_intervals: Vec<Duration>,
+
+    /// File with additional base tokens for finding token owners in liquidity
+    /// pools, one address per line. The file gets read periodically so base
+    /// tokens can be added without restarting.
+    #[clap(long, env)]
+    pub token_owner_finder_base_tokens_file: Option<PathBuf>,
+
+    /// How often the base tokens file gets read.
+    #[clap(long, env, default_value = "60", value_parser = duration_from_seconds)]
+    pub token_owner_finder_base_tokens_reload_interval: Duration,
 }
 
 fn parse_owners(s: &str) -> Result<HashMap<H160, Vec<H160>>> {
@@ -190,6 +201,16 @@ impl Display for Arguments {
             "solver_token_owners_cache_update_intervals, {:?}",
             self.solver_token_owners_cache_update_intervals
         )?;
+        writeln!(
+            f,
+            "token_owner_finder_base_tokens_file: {:?}",
+            self.token_owner_finder_base_tokens_file
+        )?;
+        writeln!(
+            f,
+            "token_owner_finder_base_tokens_reload_interval: {:?}",
+            self.token_owner_finder_base_tokens_reload_interval
+        )?;
         Ok(())
     }
 }
@@ -204,7 +225,7 @@ pub async fn init(
     pair_providers: &[PairProvider],
     vault: Option<&BalancerV2Vault>,
     uniswapv3_factory: Option<&IUniswapV3Factory>,
-    base_tokens: &BaseTokens,
+    base_tokens: &BaseTokenList,
 ) -> Result<Arc<dyn TokenOwnerFinding>> {
     let finders = args
         .token_owner_finders
@@ -215,13 +236,19 @@ pub async fn init(
     let mut proposers = Vec::<Arc<dyn TokenOwnerProposing>>::new();
 
     if finders.contains(&TokenOwnerFindingStrategy::Liquidity) {
+        if let Some(path) = &args.token_owner_finder_base_tokens_file {
+            base_tokens.spawn_reload(
+                path.clone(),
+                args.token_owner_finder_base_tokens_reload_interval,
+            );
+        }
         proposers.extend(
             pair_providers
                 .iter()
                 .map(|provider| -> Arc<dyn TokenOwnerProposing> {
                     Arc::new(UniswapLikePairProviderFinder {
                         inner: *provider,
-                        base_tokens: base_tokens.tokens().iter().copied().collect(),
+                        base_tokens: base_tokens.clone(),
                     })
                 }),
         );
@@ -232,7 +259,7 @@ pub async fn init(
             proposers.push(Arc::new(
                 UniswapV3Finder::new(
                     contract.clone(),
-                    base_tokens.tokens().iter().copied().collect(),
+                    base_tokens.clone(),
                     args.token_owner_finder_uniswap_v3_fee_values,
                 )
                 .await?,
@@ -3,15 +3,89 @@
 use {
     super::TokenOwnerProposing,
     crate::sources::{uniswap_v2::pair_provider::PairProvider, uniswap_v3_pair_provider},
-    anyhow::Result,
+    anyhow::{Context, Result},
     contracts::{BalancerV2Vault, IUniswapV3Factory},
     ethcontract::{BlockNumber, H160},
     model::TokenPair,
+    std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::Duration,
+    },
+    tokio::{sync::watch, task::JoinHandle},
 };
 
+/// Base tokens the liquidity based finders pair a token with to find pools
+/// holding it. The list is shared between clones and can be swapped at runtime,
+/// the next lookup picks up the new tokens.
+#[derive(Clone, Debug)]
+pub struct BaseTokenList(Arc<watch::Sender<Vec<H160>>>);
+
+impl BaseTokenList {
+    pub fn new(tokens: Vec<H160>) -> Self {
+        Self(Arc::new(watch::channel(tokens).0))
+    }
+
+    /// Replaces the base tokens and returns the previous ones.
+    pub fn swap(&self, tokens: Vec<H160>) -> Vec<H160> {
+        self.0.send_replace(tokens)
+    }
+
+    pub fn tokens(&self) -> Vec<H160> {
+        self.0.borrow().clone()
+    }
+
+    /// Spawns a task that periodically reads additional base tokens from a
+    /// file and uses them together with the current base tokens. This allows
+    /// adding base tokens without restarting.
+    pub fn spawn_reload(&self, path: PathBuf, interval: Duration) -> JoinHandle<()> {
+        let list = self.clone();
+        let initial = self.tokens();
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = list.reload(&initial, &path).await {
+                    tracing::warn!(?err, ?path, "failed to reload base tokens");
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    async fn reload(&self, initial: &[H160], path: &Path) -> Result<()> {
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .context("failed to read base tokens file")?;
+        let mut tokens = initial.to_vec();
+        for token in parse_base_tokens(&contents)? {
+            if !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+        if tokens != self.tokens() {
+            tracing::info!(?tokens, "updating base tokens");
+            self.swap(tokens);
+        }
+        Ok(())
+    }
+}
+
+/// Parses one token address per line. Empty lines and lines starting with `#`
+/// are ignored.
+fn parse_base_tokens(contents: &str) -> Result<Vec<H160>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.parse()
+                .with_context(|| format!("invalid base token {line:?}"))
+        })
+        .collect()
+}
+
 pub struct UniswapLikePairProviderFinder {
     pub inner: PairProvider,
-    pub base_tokens: Vec<H160>,
+    pub base_tokens: BaseTokenList,
 }
 
 #[async_trait::async_trait]
@@ -19,8 +93,9 @@ impl TokenOwnerProposing for UniswapLikePairProviderFinder {
     async fn find_candidate_owners(&self, token: H160) -> Result<Vec<H160>> {
         Ok(self
             .base_tokens
-            .iter()
-            .filter_map(|&base_token| TokenPair::new(base_token, token))
+            .tokens()
+            .into_iter()
+            .filter_map(|base_token| TokenPair::new(base_token, token))
             .map(|pair| self.inner.pair_address(&pair))
             .collect())
     }
@@ -38,7 +113,7 @@ impl TokenOwnerProposing for BalancerVaultFinder {
 
 pub struct UniswapV3Finder {
     pub factory: IUniswapV3Factory,
-    pub base_tokens: Vec<H160>,
+    pub base_tokens: BaseTokenList,
     fee_values: Vec<u32>,
 }
 
@@ -55,7 +130,7 @@ pub enum FeeValues {
 impl UniswapV3Finder {
     pub async fn new(
         factory: IUniswapV3Factory,
-        base_tokens: Vec<H160>,
+        base_tokens: BaseTokenList,
         fee_values: FeeValues,
     ) -> Result<Self> {
         let fee_values = match fee_values {
@@ -95,8 +170,9 @@ impl TokenOwnerProposing for UniswapV3Finder {
     async fn find_candidate_owners(&self, token: H160) -> Result<Vec<H160>> {
         Ok(self
             .base_tokens
-            .iter()
-            .filter_map(|base_token| TokenPair::new(*base_token, token))
+            .tokens()
+            .into_iter()
+            .filter_map(|base_token| TokenPair::new(base_token, token))
             .flat_map(|pair| self.fee_values.iter().map(move |fee| (pair, *fee)))
             .map(|(pair, fee)| {
                 uniswap_v3_pair_provider::pair_address(&self.factory.address(), &pair, fee)
@@ -104,3 +180,81 @@ impl TokenOwnerProposing for UniswapV3Finder {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::addr};
+
+    #[tokio::test]
+    async fn swapped_base_tokens_are_used() {
+        let token = H160([1; 20]);
+        let base_token = H160([2; 20]);
+        let base_tokens = BaseTokenList::new(vec![token]);
+        let finder = UniswapLikePairProviderFinder {
+            inner: PairProvider {
+                factory: H160([3; 20]),
+                init_code_digest: [4; 32],
+            },
+            base_tokens: base_tokens.clone(),
+        };
+
+        // a token can't be paired with itself so there is no candidate owner
+        assert!(finder
+            .find_candidate_owners(token)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(base_tokens.swap(vec![token, base_token]), vec![token]);
+        let pair = TokenPair::new(base_token, token).unwrap();
+        assert_eq!(
+            finder.find_candidate_owners(token).await.unwrap(),
+            vec![finder.inner.pair_address(&pair)]
+        );
+    }
+
+    #[test]
+    fn parses_base_tokens() {
+        let contents = "
+            # WETH
+            0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+
+            0x0202020202020202020202020202020202020202
+        ";
+        assert_eq!(
+            parse_base_tokens(contents).unwrap(),
+            vec![
+                addr!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+                H160([2; 20])
+            ]
+        );
+        assert!(parse_base_tokens("0x01").is_err());
+    }
+
+    #[tokio::test]
+    async fn reloads_base_tokens_from_file() {
+        let path = std::env::temp_dir().join(format!(
+            "reloads_base_tokens_from_file_{}",
+            std::process::id()
+        ));
+        let base_tokens = BaseTokenList::new(vec![H160([1; 20])]);
+        let initial = base_tokens.tokens();
+
+        std::fs::write(&path, "0x0202020202020202020202020202020202020202").unwrap();
+        base_tokens.reload(&initial, &path).await.unwrap();
+        assert_eq!(base_tokens.tokens(), vec![H160([1; 20]), H160([2; 20])]);
+
+        // Tokens removed from the file get removed again but the initial ones
+        // stay.
+        std::fs::write(&path, "0x0101010101010101010101010101010101010101").unwrap();
+        base_tokens.reload(&initial, &path).await.unwrap();
+        assert_eq!(base_tokens.tokens(), vec![H160([1; 20])]);
+
+        // A broken file keeps the current tokens.
+        std::fs::write(&path, "not a token").unwrap();
+        assert!(base_tokens.reload(&initial, &path).await.is_err());
+        assert_eq!(base_tokens.tokens(), vec![H160([1; 20])]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
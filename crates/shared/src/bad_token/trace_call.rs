@@ -19,12 +19,20 @@ use {
 /// - we cannot find an amm pool of the token to one of the base tokens
 /// - transfer into the settlement contract or back out fails
 /// - a transfer loses total balance
+/// - transfers behave differently when repeated
 pub struct TraceCallDetector {
     pub web3: Web3,
     pub finder: Arc<dyn TokenOwnerFinding>,
     pub settlement_contract: H160,
+    /// How many times the transfers into and out of the settlement contract
+    /// get simulated. Some tokens only start misbehaving after the first
+    /// transfer.
+    pub transfer_rounds: usize,
 }
 
+/// Number of traces needed to simulate one round of transfers.
+const TRACES_PER_ROUND: usize = 7;
+
 #[async_trait::async_trait]
 impl BadTokenDetecting for TraceCallDetector {
     async fn detect(&self, token: H160) -> Result<TokenQuality> {
@@ -39,9 +47,11 @@ impl TraceCallDetector {
         // Arbitrary amount that is large enough that small relative fees should be
         // visible.
         const MIN_AMOUNT: u64 = 100_000;
+        let rounds = self.transfer_rounds.max(1);
+        let min_balance = U256::from(MIN_AMOUNT) * rounds;
         let (take_from, amount) = match self
             .finder
-            .find_owner(token, MIN_AMOUNT.into())
+            .find_owner(token, min_balance)
             .await
             .context("find_owner")?
         {
@@ -54,14 +64,15 @@ impl TraceCallDetector {
                 //   in the past
                 // - New block observed - the trace_callMany is executed on a block that came in
                 //   since we read the balance
-                let amount = cmp::max(balance / 2, MIN_AMOUNT.into());
+                // Every round transfers the amount out of the owner's balance again.
+                let amount = cmp::max(balance / (2 * rounds), MIN_AMOUNT.into());
 
                 tracing::debug!(?token, ?address, ?amount, "found owner");
                 (address, amount)
             }
             None => {
                 return Ok(TokenQuality::bad(format!(
-                    "Could not find on chain source of the token with at least {MIN_AMOUNT} \
+                    "Could not find on chain source of the token with at least {min_balance} \
                      balance.",
                 )))
             }
@@ -72,11 +83,11 @@ impl TraceCallDetector {
         // Note that gas use can depend on the recipient because for the standard
         // implementation sending to an address that does not have any balance
         // yet (implicitly 0) causes an allocation.
-        let request = self.create_trace_request(token, amount, take_from);
+        let request = self.create_trace_request(token, amount, take_from, rounds);
         let traces = trace_many::trace_many(request, &self.web3)
             .await
             .context("trace_many")?;
-        Self::handle_response(&traces, amount, take_from)
+        Ok(Self::handle_response(&traces, amount, take_from))
     }
 
     // For the out transfer we use an arbitrary address without balance to detect
@@ -88,48 +99,97 @@ impl TraceCallDetector {
             .public_address()
     }
 
-    fn create_trace_request(&self, token: H160, amount: U256, take_from: H160) -> Vec<CallRequest> {
+    fn create_trace_request(
+        &self,
+        token: H160,
+        amount: U256,
+        take_from: H160,
+        rounds: usize,
+    ) -> Vec<CallRequest> {
         let instance = ERC20::at(&self.web3, token);
+        let recipient = Self::arbitrary_recipient();
 
         let mut requests = Vec::new();
 
-        // 0
-        let tx = instance.balance_of(self.settlement_contract).m.tx;
-        requests.push(call_request(None, token, tx));
-        // 1
-        let tx = instance.transfer(self.settlement_contract, amount).tx;
-        requests.push(call_request(Some(take_from), token, tx));
-        // 2
-        let tx = instance.balance_of(self.settlement_contract).m.tx;
-        requests.push(call_request(None, token, tx));
-        // 3
-        let recipient = Self::arbitrary_recipient();
-        let tx = instance.balance_of(recipient).m.tx;
-        requests.push(call_request(None, token, tx));
-        // 4
-        let tx = instance.transfer(recipient, amount).tx;
-        requests.push(call_request(Some(self.settlement_contract), token, tx));
-        // 5
-        let tx = instance.balance_of(self.settlement_contract).m.tx;
-        requests.push(call_request(None, token, tx));
-        // 6
-        let tx = instance.balance_of(recipient).m.tx;
-        requests.push(call_request(None, token, tx));
-
-        // 7
+        // The indices are relative to the start of each round.
+        for _ in 0..rounds {
+            // 0
+            let tx = instance.balance_of(self.settlement_contract).m.tx;
+            requests.push(call_request(None, token, tx));
+            // 1
+            let tx = instance.transfer(self.settlement_contract, amount).tx;
+            requests.push(call_request(Some(take_from), token, tx));
+            // 2
+            let tx = instance.balance_of(self.settlement_contract).m.tx;
+            requests.push(call_request(None, token, tx));
+            // 3
+            let tx = instance.balance_of(recipient).m.tx;
+            requests.push(call_request(None, token, tx));
+            // 4
+            let tx = instance.transfer(recipient, amount).tx;
+            requests.push(call_request(Some(self.settlement_contract), token, tx));
+            // 5
+            let tx = instance.balance_of(self.settlement_contract).m.tx;
+            requests.push(call_request(None, token, tx));
+            // 6
+            let tx = instance.balance_of(recipient).m.tx;
+            requests.push(call_request(None, token, tx));
+        }
+
         let tx = instance.approve(recipient, U256::MAX).tx;
         requests.push(call_request(Some(self.settlement_contract), token, tx));
 
         requests
     }
 
-    fn handle_response(
+    /// Determines the token quality from the traces. Traces that can't be
+    /// interpreted indicate a problem with the node rather than the token and
+    /// result in [`TokenQuality::Unknown`].
+    fn handle_response(traces: &[BlockTrace], amount: U256, take_from: H160) -> TokenQuality {
+        Self::evaluate_traces(traces, amount, take_from)
+            .unwrap_or_else(|err| TokenQuality::unknown(format!("{err:?}")))
+    }
+
+    fn evaluate_traces(
         traces: &[BlockTrace],
         amount: U256,
         take_from: H160,
     ) -> Result<TokenQuality> {
-        ensure!(traces.len() == 8, "unexpected number of traces");
+        let (approval, rounds) = traces.split_last().context("no traces")?;
+        ensure!(
+            !rounds.is_empty() && rounds.len() % TRACES_PER_ROUND == 0,
+            "unexpected number of traces"
+        );
+
+        let total_rounds = rounds.len() / TRACES_PER_ROUND;
+        for (round, traces) in rounds.chunks(TRACES_PER_ROUND).enumerate() {
+            match Self::evaluate_transfer_round(traces, amount, take_from)? {
+                TokenQuality::Good => (),
+                TokenQuality::Bad { reason } if round > 0 => {
+                    return Ok(TokenQuality::bad(format!(
+                        "Transfer round {} of {total_rounds} behaved differently than the first \
+                         one: {reason}",
+                        round + 1,
+                    )))
+                }
+                quality => return Ok(quality),
+            }
+        }
+
+        if let Err(err) = ensure_transaction_ok_and_get_gas(approval)? {
+            return Ok(TokenQuality::bad(format!(
+                "Approval of U256::MAX failed: {err}"
+            )));
+        }
+
+        Ok(TokenQuality::Good)
+    }
 
+    fn evaluate_transfer_round(
+        traces: &[BlockTrace],
+        amount: U256,
+        take_from: H160,
+    ) -> Result<TokenQuality> {
         let gas_in = match ensure_transaction_ok_and_get_gas(&traces[1])? {
             Ok(gas) => gas,
             Err(reason) => {
@@ -227,12 +287,6 @@ impl TraceCallDetector {
             )));
         }
 
-        if let Err(err) = ensure_transaction_ok_and_get_gas(&traces[7])? {
-            return Ok(TokenQuality::bad(format!(
-                "Approval of U256::MAX failed: {err}"
-            )));
-        }
-
         let _gas_per_transfer = (gas_in + gas_out) / 2;
         Ok(TokenQuality::Good)
     }
@@ -287,6 +341,7 @@ mod tests {
                 blockscout::BlockscoutTokenOwnerFinder,
                 liquidity::{
                     BalancerVaultFinder,
+                    BaseTokenList,
                     FeeValues,
                     UniswapLikePairProviderFinder,
                     UniswapV3Finder,
@@ -433,11 +488,88 @@ mod tests {
             },
         ];
 
-        let result = TraceCallDetector::handle_response(traces, 1.into(), H160::zero()).unwrap();
+        let result = TraceCallDetector::handle_response(traces, 1.into(), H160::zero());
         let expected = TokenQuality::Good;
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn handle_response_bad_if_later_round_differs() {
+        let balance = |balance: u64| BlockTrace {
+            output: encode_u256(balance.into()),
+            trace: None,
+            vm_trace: None,
+            state_diff: None,
+            transaction_hash: None,
+        };
+        let call = || BlockTrace {
+            output: Default::default(),
+            trace: Some(vec![TransactionTrace {
+                trace_address: Vec::new(),
+                subtraces: 0,
+                action: Action::Call(Call {
+                    from: H160::zero(),
+                    to: H160::zero(),
+                    value: 0.into(),
+                    gas: 0.into(),
+                    input: Bytes(Vec::new()),
+                    call_type: CallType::None,
+                }),
+                action_type: ActionType::Call,
+                result: Some(Res::Call(CallResult {
+                    gas_used: 1.into(),
+                    output: Bytes(Vec::new()),
+                })),
+                error: None,
+            }]),
+            vm_trace: None,
+            state_diff: None,
+            transaction_hash: None,
+        };
+        let round = |recipient_before: u64, recipient_after: u64| {
+            vec![
+                balance(0),
+                call(),
+                balance(1),
+                balance(recipient_before),
+                call(),
+                balance(0),
+                balance(recipient_after),
+            ]
+        };
+
+        let traces = [round(0, 1), round(1, 2), vec![call()]].concat();
+        let result = TraceCallDetector::handle_response(&traces, 1.into(), H160::zero());
+        assert_eq!(result, TokenQuality::Good);
+
+        // the second transfer to the recipient takes a fee
+        let traces = [round(0, 1), round(1, 1), vec![call()]].concat();
+        let result = TraceCallDetector::handle_response(&traces, 1.into(), H160::zero());
+        assert!(result
+            .bad_reason()
+            .unwrap()
+            .starts_with("Transfer round 2 of 2 behaved differently"));
+    }
+
+    #[test]
+    fn handle_response_unknown_on_malformed_traces() {
+        let trace = BlockTrace {
+            output: encode_u256(0.into()),
+            trace: None,
+            vm_trace: None,
+            state_diff: None,
+            transaction_hash: None,
+        };
+
+        let result = TraceCallDetector::handle_response(&[], 1.into(), H160::zero());
+        assert!(matches!(result, TokenQuality::Unknown { .. }));
+
+        // transfer traces are missing
+        let traces = vec![trace; 8];
+        let result = TraceCallDetector::handle_response(&traces, 1.into(), H160::zero());
+        assert!(matches!(result, TokenQuality::Unknown { .. }));
+    }
+
     #[test]
     fn arbitrary_recipient_() {
         println!("{:?}", TraceCallDetector::arbitrary_recipient());
@@ -638,7 +770,7 @@ mod tests {
                     .await
                     .unwrap()
                     .pair_provider,
-                    base_tokens: base_tokens.to_vec(),
+                    base_tokens: BaseTokenList::new(base_tokens.to_vec()),
                 }),
                 Arc::new(UniswapLikePairProviderFinder {
                     inner: uniswap_v2::UniV2BaselineSourceParameters::from_baseline_source(
@@ -650,7 +782,7 @@ mod tests {
                     .await
                     .unwrap()
                     .pair_provider,
-                    base_tokens: base_tokens.to_vec(),
+                    base_tokens: BaseTokenList::new(base_tokens.to_vec()),
                 }),
                 Arc::new(BalancerVaultFinder(
                     BalancerV2Vault::deployed(&web3).await.unwrap(),
@@ -658,7 +790,7 @@ mod tests {
                 Arc::new(
                     UniswapV3Finder::new(
                         IUniswapV3Factory::deployed(&web3).await.unwrap(),
-                        base_tokens.to_vec(),
+                        BaseTokenList::new(base_tokens.to_vec()),
                         FeeValues::Dynamic,
                     )
                     .await
@@ -674,6 +806,7 @@ mod tests {
             web3,
             finder,
             settlement_contract: settlement.address(),
+            transfer_rounds: 1,
         };
 
         println!("testing good tokens");
@@ -699,7 +832,7 @@ mod tests {
         let settlement = contracts::GPv2Settlement::deployed(&web3).await.unwrap();
         let factory = IUniswapV3Factory::deployed(&web3).await.unwrap();
         let univ3 = Arc::new(
-            UniswapV3Finder::new(factory, base_tokens, FeeValues::Dynamic)
+            UniswapV3Finder::new(factory, BaseTokenList::new(base_tokens), FeeValues::Dynamic)
                 .await
                 .unwrap(),
         );
@@ -711,6 +844,7 @@ mod tests {
             web3,
             finder,
             settlement_contract: settlement.address(),
+            transfer_rounds: 1,
         };
 
         let result = token_cache.detect(testlib::tokens::USDC).await;
@@ -836,6 +970,7 @@ mod tests {
             web3,
             finder,
             settlement_contract: settlement.address(),
+            transfer_rounds: 1,
         };
 
         for token in tokens {
@@ -502,13 +502,21 @@ impl OrderValidating for OrderValidator {
         }
 
         for &token in &[order.sell_token, order.buy_token] {
-            if let TokenQuality::Bad { reason } = self
+            match self
                 .bad_token_detector
                 .detect(token)
                 .await
                 .map_err(PartialValidationError::Other)?
             {
-                return Err(PartialValidationError::UnsupportedToken { token, reason });
+                TokenQuality::Good => (),
+                TokenQuality::Bad { reason } => {
+                    return Err(PartialValidationError::UnsupportedToken { token, reason })
+                }
+                TokenQuality::Unknown { reason } => {
+                    return Err(PartialValidationError::Other(anyhow!(
+                        "unknown quality of token {token:?}: {reason}"
+                    )))
+                }
             }
         }
 
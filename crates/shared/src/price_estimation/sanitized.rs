@@ -45,7 +45,12 @@ impl SanitizedPriceEstimator {
                 Ok(TokenQuality::Bad { reason }) => {
                     return Err(PriceEstimationError::UnsupportedToken { token, reason })
                 }
-                _ => (),
+                Ok(TokenQuality::Unknown { reason }) => {
+                    return Err(PriceEstimationError::ProtocolInternal(anyhow!(
+                        "unknown quality of token {token:?}: {reason}"
+                    )))
+                }
+                Ok(TokenQuality::Good) => (),
             }
         }
         Ok(())
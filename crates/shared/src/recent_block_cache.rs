@@ -29,27 +29,52 @@ use {
     anyhow::{Context, Result},
     cached::{Cached, SizedCache},
     ethcontract::BlockNumber,
-    ethrpc::current_block::CurrentBlockStream,
-    futures::FutureExt,
+    ethrpc::current_block::{into_stream, CurrentBlockStream},
+    futures::{FutureExt, StreamExt, TryStreamExt},
     itertools::Itertools,
-    prometheus::IntCounterVec,
+    prometheus::{HistogramVec, IntCounterVec},
     std::{
         cmp,
         collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
         hash::Hash,
         num::{NonZeroU64, NonZeroUsize},
+        ops::RangeInclusive,
         sync::{Arc, Mutex},
-        time::Duration,
+        time::{Duration, Instant},
     },
+    tokio::task::JoinHandle,
+    tracing::Instrument,
 };
 
 /// How many liqudity sources should at most be fetched in a single chunk.
 const REQUEST_BATCH_SIZE: usize = 200;
 
+/// How many blocks can at most be fetched with a single
+/// [`RecentBlockCache::fetch_range`] call.
+const MAX_FETCH_RANGE_BLOCKS: u64 = 1000;
+
 /// A trait used to define `RecentBlockCache` updating behaviour.
 #[async_trait::async_trait]
 pub trait CacheFetching<K, V>: Send + Sync + 'static {
-    async fn fetch_values(&self, keys: HashSet<K>, block: Block) -> Result<Vec<V>>;
+    async fn fetch_values(&self, keys: HashSet<K>, block: Block) -> Result<Vec<V>, FetchError>;
+}
+
+/// Errors of [`CacheFetching`] implementations. They determine whether
+/// `RecentBlockCache` retries a failed fetch.
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    /// The node has not seen the requested block yet, for example because the
+    /// request was load balanced to a node that is lagging behind. Retrying
+    /// later can succeed.
+    #[error("block not yet available: {0:?}")]
+    BlockNotYetAvailable(anyhow::Error),
+    /// The node can never serve the requested block, for example because its
+    /// state has been pruned. Retrying is futile.
+    #[error("block pruned: {0:?}")]
+    BlockPruned(anyhow::Error),
+    /// Any other error. These are retried.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
 }
 
 /// A trait used for `RecentBlockCache` keys.
@@ -69,13 +94,17 @@ pub enum Block {
     /// example a cache can still return results that are slightly out of
     /// date.
     Recent,
+    /// Like [`Block::Recent`] but cached data may be at most this many blocks
+    /// older than the last cache update. This overrides the cache's
+    /// configured maximum recent block age for a single request.
+    RecentWithin(u64),
     Number(u64),
 }
 
 impl From<Block> for BlockNumber {
     fn from(val: Block) -> Self {
         match val {
-            Block::Recent => BlockNumber::Latest,
+            Block::Recent | Block::RecentWithin(_) => BlockNumber::Latest,
             Block::Number(number) => BlockNumber::Number(number.into()),
         }
     }
@@ -99,7 +128,7 @@ where
     delay_between_retries: Duration,
     metrics: &'static Metrics,
     metrics_label: &'static str,
-    requests: BoxRequestSharing<(K, Block), Option<Vec<V>>>,
+    requests: BoxRequestSharing<(K, Block), Result<Vec<V>, Arc<FetchError>>>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -109,6 +138,10 @@ pub struct CacheConfig {
     pub maximum_recent_block_age: u64,
     pub max_retries: u32,
     pub delay_between_retries: Duration,
+    /// If set, cached entries without any values expire after this duration
+    /// even if their block is still cached. This allows discovering newly
+    /// created liquidity sources sooner.
+    pub negative_ttl: Option<Duration>,
 }
 
 impl Default for CacheConfig {
@@ -119,6 +152,7 @@ impl Default for CacheConfig {
             maximum_recent_block_age: Default::default(),
             max_retries: Default::default(),
             delay_between_retries: Default::default(),
+            negative_ttl: Default::default(),
         }
     }
 }
@@ -132,6 +166,22 @@ struct Metrics {
     /// misses
     #[metric(labels("cache_type"))]
     recent_block_cache_misses: IntCounterVec,
+
+    /// Duration of fetching values from the node.
+    #[metric(
+        labels("cache_type"),
+        buckets(0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1, 2.5, 5, 10)
+    )]
+    recent_block_cache_fetch_seconds: HistogramVec,
+
+    /// Number of evicted cache entries by reason. Entries either get evicted
+    /// because their block became too old (`age`) or because they were pushed
+    /// out of the recently used entries (`lru`).
+    #[metric(
+        name = "recent_block_cache_evictions_total",
+        labels("cache_type", "reason")
+    )]
+    recent_block_cache_evictions: IntCounterVec,
 }
 
 impl<K, V, F> RecentBlockCache<K, V, F>
@@ -158,18 +208,22 @@ where
         metrics_label: &'static str,
     ) -> Result<Self> {
         let block = block_stream.borrow().number;
+        let metrics = Metrics::instance(observe::metrics::get_storage_registry()).unwrap();
         Ok(Self {
             mutexed: Mutex::new(Mutexed::new(
                 config.number_of_entries_to_auto_update,
                 block,
                 config.maximum_recent_block_age,
+                config.negative_ttl,
+                metrics,
+                metrics_label,
             )),
             number_of_blocks_to_cache: config.number_of_blocks_to_cache,
             fetcher: Arc::new(fetcher),
             block_stream,
             maximum_retries: config.max_retries,
             delay_between_retries: config.delay_between_retries,
-            metrics: Metrics::instance(observe::metrics::get_storage_registry()).unwrap(),
+            metrics,
             metrics_label,
             requests: BoxRequestSharing::labelled("liquidity_fetching".into()),
         })
@@ -180,6 +234,45 @@ where
         self.update_cache_at_block(new_block).await
     }
 
+    /// Returns the sorted block numbers for which entries of the given key are
+    /// cached. Useful for debugging unexpected cache misses.
+    pub fn cached_blocks(&self, key: &K) -> Vec<u64> {
+        self.mutexed.lock().unwrap().cached_blocks(key)
+    }
+
+    /// Pins the given keys so they always get updated automatically and never
+    /// get evicted from the recently used entries, regardless of how often
+    /// they are requested. This is useful for keeping the most valuable
+    /// entries warm.
+    pub fn pin_keys(&self, keys: impl IntoIterator<Item = K>) {
+        self.mutexed.lock().unwrap().pin(keys);
+    }
+
+    /// Drops all cached entries and forgets which entries were recently used.
+    /// Pinned keys stay pinned. This is useful after a deep reorg which could
+    /// have invalidated the cached data.
+    pub fn clear(&self) {
+        let current_block = self.block_stream.borrow().number;
+        self.mutexed.lock().unwrap().clear(current_block);
+    }
+
+    /// Spawns a background task that updates the cache on every new block.
+    /// This is an alternative to manually driving the cache with
+    /// [`Self::update_cache`].
+    pub fn spawn_auto_update(self: Arc<Self>) -> JoinHandle<()> {
+        let span = tracing::info_span!("recent_block_cache", label = self.metrics_label);
+        let mut stream = into_stream(self.block_stream.clone());
+        let task = async move {
+            while let Some(block) = stream.next().await {
+                if let Err(err) = self.update_cache_at_block(block.number).await {
+                    tracing::warn!(?err, block = block.number, "failed to update cache");
+                }
+            }
+            tracing::error!("block stream terminated unexpectedly");
+        };
+        tokio::spawn(task.instrument(span))
+    }
+
     async fn update_cache_at_block(&self, new_block: u64) -> Result<()> {
         let keys = self
             .mutexed
@@ -202,49 +295,56 @@ where
     }
 
     async fn fetch_inner_many(&self, keys: HashSet<K>, block: Block) -> Result<Vec<V>> {
-        let fetched =
-            futures::future::join_all(keys.iter().map(|key| self.fetch_inner(key.clone(), block)))
-                .await;
-        let fetched: Vec<_> = fetched
-            .into_iter()
-            .filter_map(|res| res.ok())
-            .flatten()
-            .collect();
-        Ok(fetched)
+        // Bounds the number of concurrent requests because automatically updating
+        // all recently used entries would otherwise flood the node on every block.
+        let fetched: Vec<_> = futures::stream::iter(keys)
+            .map(|key| self.fetch_inner(key, block))
+            .buffer_unordered(REQUEST_BATCH_SIZE)
+            .try_collect()
+            .await?;
+        Ok(fetched.into_iter().flatten().collect())
     }
 
     // Sometimes nodes requests error when we try to get state from what we think is
     // the current block when the node has been load balanced out to one that
     // hasn't seen the block yet. As a workaround we repeat the request up to N
-    // times while sleeping in between.
+    // times while sleeping in between. Blocks that were pruned are never going to
+    // become available so those errors are returned right away.
     async fn fetch_inner(&self, key: K, block: Block) -> Result<Vec<V>> {
         let retries = self.maximum_retries;
         let delay = self.delay_between_retries;
         let fetcher = self.fetcher.clone();
+        let (metrics, metrics_label) = (self.metrics, self.metrics_label);
         let fut = self.requests.shared_or_else((key, block), |entry| {
             let (key, block) = entry.clone();
             async move {
-                for _ in 0..=retries {
+                let mut attempt = 0;
+                loop {
                     let keys = [key.clone()].into();
-                    match fetcher.fetch_values(keys, block).await {
-                        Ok(values) => return Some(values),
+                    let start = Instant::now();
+                    let result = fetcher.fetch_values(keys, block).await;
+                    metrics
+                        .recent_block_cache_fetch_seconds
+                        .with_label_values(&[metrics_label])
+                        .observe(start.elapsed().as_secs_f64());
+                    match result {
+                        Ok(values) => return Ok(values),
+                        Err(err @ FetchError::BlockPruned(_)) => return Err(Arc::new(err)),
+                        Err(err) if attempt >= retries => return Err(Arc::new(err)),
                         Err(err) => tracing::warn!("retrying fetch because error: {:?}", err),
                     }
+                    attempt += 1;
                     tokio::time::sleep(delay).await;
                 }
-                None
             }
             .boxed()
         });
-        fut.await.context("could not fetch liquidity")
+        fut.await
+            .map_err(anyhow::Error::new)
+            .context("could not fetch liquidity")
     }
 
     pub async fn fetch(&self, keys: impl IntoIterator<Item = K>, block: Block) -> Result<Vec<V>> {
-        let block = match block {
-            Block::Recent => None,
-            Block::Number(number) => Some(number),
-        };
-
         let mut cache_hit_count = 0usize;
         let mut cache_hits = Vec::new();
         let mut cache_misses = HashSet::new();
@@ -278,7 +378,10 @@ where
             return Ok(cache_hits);
         }
 
-        let cache_miss_block = block.unwrap_or(last_update_block);
+        let cache_miss_block = match block {
+            Block::Number(number) => number,
+            Block::Recent | Block::RecentWithin(_) => last_update_block,
+        };
         let cache_misses: Vec<_> = cache_misses.into_iter().collect();
         // Splits fetches into chunks because we can get over 1400 requests when the
         // cache is empty which tend to time out if we don't chunk them.
@@ -293,12 +396,44 @@ where
             let mut mutexed = self.mutexed.lock().unwrap();
             mutexed.insert(cache_miss_block, chunk.iter().cloned(), fetched);
             for key in found_keys {
-                mutexed.recently_used.cache_set(key, ());
+                mutexed.mark_recently_used(key);
             }
         }
 
         Ok(cache_hits)
     }
+
+    /// Fetches the values of the given keys at every block of the range and
+    /// caches them like [`Self::fetch`] does. Multiple blocks get fetched
+    /// concurrently which makes this useful for backfilling historic data.
+    /// The range may span at most `MAX_FETCH_RANGE_BLOCKS` blocks.
+    pub async fn fetch_range(
+        &self,
+        keys: impl IntoIterator<Item = K>,
+        blocks: RangeInclusive<u64>,
+    ) -> Result<HashMap<u64, Vec<V>>> {
+        let len = if blocks.is_empty() {
+            0
+        } else {
+            blocks.end() - blocks.start() + 1
+        };
+        anyhow::ensure!(
+            len <= MAX_FETCH_RANGE_BLOCKS,
+            "block range {blocks:?} spans more than {MAX_FETCH_RANGE_BLOCKS} blocks"
+        );
+        let keys = keys.into_iter().collect_vec();
+        // Only fetch as many blocks at once as it takes to have about
+        // `REQUEST_BATCH_SIZE` requests in flight.
+        let concurrent_blocks = (REQUEST_BATCH_SIZE / keys.len().max(1)).max(1);
+        futures::stream::iter(blocks)
+            .map(|block| {
+                self.fetch(keys.iter().cloned(), Block::Number(block))
+                    .map(move |values| values.map(|values| (block, values)))
+            })
+            .buffered(concurrent_blocks)
+            .try_collect()
+            .await
+    }
 }
 
 #[derive(Debug)]
@@ -307,6 +442,9 @@ where
     K: CacheKey<V>,
 {
     recently_used: SizedCache<K, ()>,
+    // Keys that always get updated and are never evicted. They are not part of
+    // `recently_used` so they don't take up any of its capacity.
+    pinned: HashSet<K>,
     // For quickly finding at which block an entry is cached.
     cached_most_recently_at_block: HashMap<K, u64>,
     // Tuple ordering allows us to efficiently construct range queries by block.
@@ -315,6 +453,13 @@ where
     last_update_block: u64,
     // Maximum age a cached block can have to count as recent.
     maximum_recent_block_age: u64,
+    // When entries without values were inserted. Only used if `negative_ttl`
+    // is set.
+    empty_entries_inserted_at: HashMap<(u64, K), Instant>,
+    // How long entries without values stay valid.
+    negative_ttl: Option<Duration>,
+    metrics: &'static Metrics,
+    metrics_label: &'static str,
 }
 
 impl<K, V> Mutexed<K, V>
@@ -325,30 +470,78 @@ where
         entries_lru_size: NonZeroUsize,
         current_block: u64,
         maximum_recent_block_age: u64,
+        negative_ttl: Option<Duration>,
+        metrics: &'static Metrics,
+        metrics_label: &'static str,
     ) -> Self {
         Self {
             recently_used: SizedCache::with_size(entries_lru_size.get()),
+            pinned: HashSet::new(),
             cached_most_recently_at_block: HashMap::new(),
             entries: BTreeMap::new(),
             last_update_block: current_block,
             maximum_recent_block_age,
+            empty_entries_inserted_at: HashMap::new(),
+            negative_ttl,
+            metrics,
+            metrics_label,
         }
     }
 
-    fn get(&mut self, key: K, block: Option<u64>) -> Option<&[V]> {
-        let block = block.or_else(|| {
-            self.cached_most_recently_at_block
-                .get(&key)
-                .copied()
-                .filter(|&block| {
-                    self.last_update_block.saturating_sub(block) <= self.maximum_recent_block_age
-                })
-        })?;
-        let result = self.entries.get(&(block, key.clone())).map(Vec::as_slice);
-        if result.is_some_and(|values| !values.is_empty()) {
-            self.recently_used.cache_set(key, ());
+    fn get(&mut self, key: K, block: Block) -> Option<&[V]> {
+        let block = match block {
+            Block::Number(number) => number,
+            Block::Recent => self.most_recent_block(&key, self.maximum_recent_block_age)?,
+            Block::RecentWithin(maximum_age) => self.most_recent_block(&key, maximum_age)?,
+        };
+        if self.is_expired_empty_entry(&(block, key.clone())) {
+            return None;
+        }
+        let entry = (block, key);
+        if self
+            .entries
+            .get(&entry)
+            .is_some_and(|values| !values.is_empty())
+        {
+            self.mark_recently_used(entry.1.clone());
+        }
+        self.entries.get(&entry).map(Vec::as_slice)
+    }
+
+    /// The most recent block at which the key is cached if it is at most
+    /// `maximum_age` blocks older than the last update.
+    fn most_recent_block(&self, key: &K, maximum_age: u64) -> Option<u64> {
+        self.cached_most_recently_at_block
+            .get(key)
+            .copied()
+            .filter(|&block| self.last_update_block.saturating_sub(block) <= maximum_age)
+    }
+
+    fn mark_recently_used(&mut self, key: K) {
+        if self.pinned.contains(&key) {
+            return;
+        }
+        // `SizedCache` doesn't tell us which key it displaced but inserting a new
+        // key into a full cache always evicts the least recently used one.
+        let is_full = self.recently_used.cache_capacity() == Some(self.recently_used.cache_size());
+        if self.recently_used.cache_set(key, ()).is_none() && is_full {
+            tracing::debug!("evicted least recently used entry from cache");
+            self.record_evictions("lru", 1);
+        }
+    }
+
+    fn record_evictions(&self, reason: &str, count: usize) {
+        self.metrics
+            .recent_block_cache_evictions
+            .with_label_values(&[self.metrics_label, reason])
+            .inc_by(count as u64);
+    }
+
+    fn pin(&mut self, keys: impl IntoIterator<Item = K>) {
+        for key in keys {
+            self.recently_used.cache_remove(&key);
+            self.pinned.insert(key);
         }
-        result
     }
 
     fn insert(
@@ -357,7 +550,8 @@ where
         keys: impl IntoIterator<Item = K>,
         values: impl IntoIterator<Item = V>,
     ) {
-        for key in keys {
+        let keys = keys.into_iter().collect_vec();
+        for key in &keys {
             match self.cached_most_recently_at_block.entry(key.clone()) {
                 Entry::Occupied(mut entry) => {
                     let value = entry.get_mut();
@@ -368,7 +562,7 @@ where
                 }
             }
             // Make sure entries without any values are cached.
-            self.entries.insert((block, key), Vec::new());
+            self.entries.insert((block, key.clone()), Vec::new());
         }
         for value in values {
             // Unwrap because previous loop guarantees all keys have an entry.
@@ -377,21 +571,68 @@ where
                 .unwrap()
                 .push(value);
         }
+        if self.negative_ttl.is_some() {
+            let now = Instant::now();
+            for key in keys {
+                let entry = (block, key);
+                if self.entries[&entry].is_empty() {
+                    self.empty_entries_inserted_at.insert(entry, now);
+                } else {
+                    self.empty_entries_inserted_at.remove(&entry);
+                }
+            }
+        }
+    }
+
+    fn is_expired_empty_entry(&self, entry: &(u64, K)) -> bool {
+        match (self.negative_ttl, self.empty_entries_inserted_at.get(entry)) {
+            (Some(ttl), Some(inserted_at)) => inserted_at.elapsed() >= ttl,
+            _ => false,
+        }
     }
 
     fn remove_cached_blocks_older_than(&mut self, oldest_to_keep: u64) {
-        tracing::debug!("dropping blocks older than {} from cache", oldest_to_keep);
-        self.entries = self.entries.split_off(&(oldest_to_keep, K::first_ord()));
+        let kept = self.entries.split_off(&(oldest_to_keep, K::first_ord()));
+        let evicted = std::mem::replace(&mut self.entries, kept).len();
+        tracing::debug!(
+            "dropped {} block-key combinations older than block {} from cache",
+            evicted,
+            oldest_to_keep
+        );
+        self.record_evictions("age", evicted);
         self.cached_most_recently_at_block
             .retain(|_, block| *block >= oldest_to_keep);
+        self.empty_entries_inserted_at
+            .retain(|(block, _), _| *block >= oldest_to_keep);
         tracing::debug!(
             "the cache now contains entries for {} block-key combinations",
             self.entries.len()
         );
     }
 
+    fn clear(&mut self, current_block: u64) {
+        tracing::debug!("clearing {} block-key combinations", self.entries.len());
+        self.recently_used.cache_clear();
+        self.cached_most_recently_at_block.clear();
+        self.entries.clear();
+        self.empty_entries_inserted_at.clear();
+        self.last_update_block = current_block;
+    }
+
+    fn cached_blocks(&self, key: &K) -> Vec<u64> {
+        // Entries are ordered by block so the result is already sorted.
+        self.entries
+            .keys()
+            .filter(|(_, cached)| cached == key)
+            .map(|(block, _)| *block)
+            .collect()
+    }
+
     fn keys_of_recently_used_entries(&self) -> impl Iterator<Item = K> + '_ {
-        self.recently_used.key_order().cloned()
+        self.pinned
+            .iter()
+            .chain(self.recently_used.key_order())
+            .cloned()
     }
 }
 
@@ -401,7 +642,10 @@ mod tests {
         super::*,
         ethrpc::current_block::{mock_single_block, BlockInfo},
         futures::FutureExt,
-        std::sync::Arc,
+        std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
     };
 
     #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -441,7 +685,7 @@ mod tests {
             &self,
             requested: HashSet<TestKey>,
             _: Block,
-        ) -> Result<Vec<TestValue>> {
+        ) -> Result<Vec<TestValue>, FetchError> {
             let fetched = self
                 .0
                 .lock()
@@ -524,6 +768,44 @@ mod tests {
         assert_eq!(keys, test_keys(1..3).collect());
     }
 
+    #[tokio::test]
+    async fn fetch_range_caches_every_block() {
+        let fetcher = FakeCacheFetcher::new(vec![TestValue::new(0, "a"), TestValue::new(1, "b")]);
+        let block_stream = mock_single_block(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                number_of_entries_to_auto_update: NonZeroUsize::new(2).unwrap(),
+                ..Default::default()
+            },
+            fetcher,
+            block_stream,
+            "",
+        )
+        .unwrap();
+
+        let result = cache
+            .fetch_range(test_keys(0..3), 5..=7)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.keys().copied().sorted().collect_vec(), [5, 6, 7]);
+        for values in result.values() {
+            assert_eq!(values.len(), 2);
+        }
+        assert_eq!(cache.cached_blocks(&TestKey(0)), [5, 6, 7]);
+        assert_eq!(cache.cached_blocks(&TestKey(1)), [5, 6, 7]);
+
+        assert!(cache
+            .fetch_range(test_keys(0..3), 0..=MAX_FETCH_RANGE_BLOCKS)
+            .now_or_never()
+            .unwrap()
+            .is_err());
+        assert_eq!(cache.cached_blocks(&TestKey(0)), [5, 6, 7]);
+    }
+
     #[tokio::test]
     async fn auto_updates_recently_used() {
         let fetcher = FakeCacheFetcher::default();
@@ -765,8 +1047,18 @@ mod tests {
             .now_or_never()
             .unwrap()
             .unwrap();
-        assert!(cache.mutexed.lock().unwrap().get(key, Some(7)).is_some());
-        assert!(cache.mutexed.lock().unwrap().get(key, None).is_none());
+        assert!(cache
+            .mutexed
+            .lock()
+            .unwrap()
+            .get(key, Block::Number(7))
+            .is_some());
+        assert!(cache
+            .mutexed
+            .lock()
+            .unwrap()
+            .get(key, Block::Recent)
+            .is_none());
 
         // cache at block 8
         cache
@@ -774,8 +1066,445 @@ mod tests {
             .now_or_never()
             .unwrap()
             .unwrap();
-        assert!(cache.mutexed.lock().unwrap().get(key, Some(7)).is_some());
-        assert!(cache.mutexed.lock().unwrap().get(key, Some(8)).is_some());
-        assert!(cache.mutexed.lock().unwrap().get(key, None).is_some());
+        assert!(cache
+            .mutexed
+            .lock()
+            .unwrap()
+            .get(key, Block::Number(7))
+            .is_some());
+        assert!(cache
+            .mutexed
+            .lock()
+            .unwrap()
+            .get(key, Block::Number(8))
+            .is_some());
+        assert!(cache
+            .mutexed
+            .lock()
+            .unwrap()
+            .get(key, Block::Recent)
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn spawned_task_updates_on_new_blocks() {
+        let fetcher = FakeCacheFetcher::new(vec![TestValue::new(0, "foo")]);
+        let values = fetcher.0.clone();
+        let (sender, block_stream) = tokio::sync::watch::channel(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = Arc::new(
+            RecentBlockCache::new(
+                CacheConfig {
+                    number_of_blocks_to_cache: NonZeroU64::new(5).unwrap(),
+                    maximum_recent_block_age: 5,
+                    ..Default::default()
+                },
+                fetcher,
+                block_stream,
+                "",
+            )
+            .unwrap(),
+        );
+        let handle = cache.clone().spawn_auto_update();
+
+        let result = cache.fetch(test_keys(0..1), Block::Recent).await.unwrap();
+        assert_eq!(result, vec![TestValue::new(0, "foo")]);
+
+        *values.lock().unwrap() = vec![TestValue::new(0, "bar")];
+        sender
+            .send(BlockInfo {
+                number: 11,
+                ..Default::default()
+            })
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while cache.mutexed.lock().unwrap().last_update_block != 11 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        values.lock().unwrap().clear();
+        let result = cache.fetch(test_keys(0..1), Block::Recent).await.unwrap();
+        assert_eq!(result, vec![TestValue::new(0, "bar")]);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn empty_entries_expire_after_negative_ttl() {
+        let new_cache = |negative_ttl| {
+            let fetcher = FakeCacheFetcher::default();
+            let values = fetcher.0.clone();
+            let block_stream = mock_single_block(BlockInfo {
+                number: 10,
+                ..Default::default()
+            });
+            let cache = RecentBlockCache::new(
+                CacheConfig {
+                    negative_ttl,
+                    ..Default::default()
+                },
+                fetcher,
+                block_stream,
+                "",
+            )
+            .unwrap();
+            (cache, values)
+        };
+        let fetch = |cache: &RecentBlockCache<_, _, _>| {
+            cache
+                .fetch(test_keys(0..1), Block::Recent)
+                .now_or_never()
+                .unwrap()
+                .unwrap()
+        };
+
+        for negative_ttl in [None, Some(Duration::from_secs(3600))] {
+            let (cache, values) = new_cache(negative_ttl);
+            assert!(fetch(&cache).is_empty());
+            // The pool got created but we keep returning the cached miss.
+            *values.lock().unwrap() = vec![TestValue::new(0, "foo")];
+            assert!(fetch(&cache).is_empty());
+        }
+
+        let (cache, values) = new_cache(Some(Duration::ZERO));
+        assert!(fetch(&cache).is_empty());
+        *values.lock().unwrap() = vec![TestValue::new(0, "foo")];
+        assert_eq!(fetch(&cache), vec![TestValue::new(0, "foo")]);
+        // Populated entries are not affected by the negative TTL.
+        values.lock().unwrap().clear();
+        assert_eq!(fetch(&cache), vec![TestValue::new(0, "foo")]);
+    }
+
+    #[tokio::test]
+    async fn pinned_keys_survive_eviction() {
+        let values = (0..10).map(|key| TestValue::new(key, "")).collect();
+        let fetcher = FakeCacheFetcher::new(values);
+        let block_stream = mock_single_block(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                number_of_entries_to_auto_update: NonZeroUsize::new(2).unwrap(),
+                ..Default::default()
+            },
+            fetcher,
+            block_stream,
+            "",
+        )
+        .unwrap();
+        cache.pin_keys(test_keys([0]));
+
+        for key in 0..10 {
+            cache
+                .fetch(test_keys([key]), Block::Recent)
+                .now_or_never()
+                .unwrap()
+                .unwrap();
+        }
+        let keys = cache
+            .mutexed
+            .lock()
+            .unwrap()
+            .keys_of_recently_used_entries()
+            .collect::<HashSet<_>>();
+        // The pinned key doesn't use up any of the LRU capacity.
+        assert_eq!(keys, test_keys([0, 8, 9]).collect());
+
+        // Pinning a key that is already recently used doesn't count it twice.
+        cache.pin_keys(test_keys([9]));
+        let keys = cache
+            .mutexed
+            .lock()
+            .unwrap()
+            .keys_of_recently_used_entries()
+            .collect_vec();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(
+            keys.into_iter().collect::<HashSet<_>>(),
+            test_keys([0, 8, 9]).collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn counts_evictions() {
+        let values = (0..3).map(|key| TestValue::new(key, "")).collect();
+        let fetcher = FakeCacheFetcher::new(values);
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                number_of_entries_to_auto_update: NonZeroUsize::new(2).unwrap(),
+                ..Default::default()
+            },
+            fetcher,
+            mock_single_block(Default::default()),
+            "counts_evictions",
+        )
+        .unwrap();
+        let evictions = |reason| {
+            cache
+                .metrics
+                .recent_block_cache_evictions
+                .with_label_values(&["counts_evictions", reason])
+                .get()
+        };
+
+        for key in 0..3 {
+            cache
+                .fetch(test_keys([key]), Block::Number(10))
+                .await
+                .unwrap();
+        }
+        assert_eq!(evictions("lru"), 1);
+        assert_eq!(evictions("age"), 0);
+
+        // Only the 2 recently used keys get updated and the 3 entries of the
+        // previous block get dropped.
+        cache.update_cache_at_block(11).await.unwrap();
+        assert_eq!(evictions("lru"), 1);
+        assert_eq!(evictions("age"), 3);
+    }
+
+    #[tokio::test]
+    async fn clear_drops_all_entries() {
+        let fetcher = FakeCacheFetcher::new(vec![TestValue::new(0, "a"), TestValue::new(1, "b")]);
+        let fetched = fetcher.0.clone();
+        let block_stream = mock_single_block(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                number_of_entries_to_auto_update: NonZeroUsize::new(2).unwrap(),
+                number_of_blocks_to_cache: NonZeroU64::new(5).unwrap(),
+                ..Default::default()
+            },
+            fetcher,
+            block_stream,
+            "",
+        )
+        .unwrap();
+
+        for block in [Block::Number(9), Block::Recent] {
+            let values = cache.fetch(test_keys(0..2), block).await.unwrap();
+            assert_eq!(values.len(), 2);
+        }
+
+        cache.clear();
+        {
+            let mutexed = cache.mutexed.lock().unwrap();
+            assert_eq!(mutexed.keys_of_recently_used_entries().count(), 0);
+            assert_eq!(mutexed.last_update_block, 10);
+        }
+        assert!(cache.cached_blocks(&TestKey(0)).is_empty());
+
+        // Every fetch misses the cache and sees the new on-chain data.
+        fetched.lock().unwrap()[0].data = "c".to_string();
+        for block in [Block::Number(9), Block::Recent] {
+            let values = cache.fetch(test_keys(0..1), block).await.unwrap();
+            assert_eq!(values, [TestValue::new(0, "c")]);
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_cached_blocks_of_key() {
+        let fetcher = FakeCacheFetcher::new(vec![TestValue::new(0, "foo")]);
+        let block_stream = mock_single_block(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                number_of_blocks_to_cache: NonZeroU64::new(5).unwrap(),
+                ..Default::default()
+            },
+            fetcher,
+            block_stream,
+            "",
+        )
+        .unwrap();
+
+        for block in [8, 6, 7] {
+            cache
+                .fetch(test_keys(0..1), Block::Number(block))
+                .now_or_never()
+                .unwrap()
+                .unwrap();
+        }
+        cache
+            .fetch(test_keys(1..2), Block::Number(9))
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(cache.cached_blocks(&TestKey(0)), vec![6, 7, 8]);
+        assert_eq!(cache.cached_blocks(&TestKey(1)), vec![9]);
+        assert!(cache.cached_blocks(&TestKey(2)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn recent_within_overrides_max_age() {
+        let fetcher = FakeCacheFetcher::new(vec![TestValue::new(0, "foo")]);
+        let block_stream = mock_single_block(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                number_of_blocks_to_cache: NonZeroU64::new(5).unwrap(),
+                maximum_recent_block_age: 2,
+                ..Default::default()
+            },
+            fetcher,
+            block_stream,
+            "",
+        )
+        .unwrap();
+        let key = TestKey(0);
+
+        // cache at block 7, most recent block is 10.
+        cache
+            .fetch(std::iter::once(key), Block::Number(7))
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+
+        let mut mutexed = cache.mutexed.lock().unwrap();
+        assert!(mutexed.get(key, Block::Recent).is_none());
+        assert!(mutexed.get(key, Block::RecentWithin(3)).is_some());
+        assert!(mutexed.get(key, Block::RecentWithin(5)).is_some());
+        assert!(mutexed.get(key, Block::RecentWithin(1)).is_none());
+    }
+
+    struct FailingCacheFetcher {
+        calls: Arc<AtomicUsize>,
+        pruned: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl CacheFetching<TestKey, TestValue> for FailingCacheFetcher {
+        async fn fetch_values(
+            &self,
+            _: HashSet<TestKey>,
+            _: Block,
+        ) -> Result<Vec<TestValue>, FetchError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.pruned {
+                Err(FetchError::BlockPruned(anyhow::anyhow!("pruned")))
+            } else {
+                Err(FetchError::BlockNotYetAvailable(anyhow::anyhow!(
+                    "not yet available"
+                )))
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct ConcurrencyTrackingFetcher {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl CacheFetching<TestKey, TestValue> for Arc<ConcurrencyTrackingFetcher> {
+        async fn fetch_values(
+            &self,
+            keys: HashSet<TestKey>,
+            _: Block,
+        ) -> Result<Vec<TestValue>, FetchError> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(keys
+                .into_iter()
+                .map(|key| TestValue::new(key.0, ""))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn auto_update_bounds_concurrent_requests() {
+        let fetcher = Arc::new(ConcurrencyTrackingFetcher::default());
+        let number_of_keys = REQUEST_BATCH_SIZE + 50;
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                number_of_entries_to_auto_update: NonZeroUsize::new(number_of_keys).unwrap(),
+                ..Default::default()
+            },
+            fetcher.clone(),
+            mock_single_block(Default::default()),
+            "",
+        )
+        .unwrap();
+        cache.pin_keys(test_keys(0..number_of_keys));
+
+        cache.update_cache_at_block(1).await.unwrap();
+        assert_eq!(cache.cached_blocks(&TestKey(0)), vec![1]);
+        assert_eq!(cache.cached_blocks(&TestKey(number_of_keys - 1)), vec![1]);
+        assert_eq!(
+            fetcher.max_in_flight.load(Ordering::SeqCst),
+            REQUEST_BATCH_SIZE
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_returns_pruned_block_errors() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let fetcher = FailingCacheFetcher {
+            calls: calls.clone(),
+            pruned: true,
+        };
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                max_retries: 3,
+                ..Default::default()
+            },
+            fetcher,
+            mock_single_block(Default::default()),
+            "",
+        )
+        .unwrap();
+
+        let err = cache
+            .fetch(test_keys(0..2), Block::Number(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Arc<FetchError>>().map(AsRef::as_ref),
+            Some(FetchError::BlockPruned(_))
+        ));
+        // Failed fetches don't get cached as if there were no values.
+        assert!(cache.cached_blocks(&TestKey(0)).is_empty());
+        assert!(cache.cached_blocks(&TestKey(1)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn only_retries_blocks_that_are_not_yet_available() {
+        for (pruned, expected_calls) in [(false, 4), (true, 1)] {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let fetcher = FailingCacheFetcher {
+                calls: calls.clone(),
+                pruned,
+            };
+            let cache = RecentBlockCache::new(
+                CacheConfig {
+                    max_retries: 3,
+                    ..Default::default()
+                },
+                fetcher,
+                mock_single_block(Default::default()),
+                "",
+            )
+            .unwrap();
+
+            let result = cache.fetch_inner(TestKey(0), Block::Number(1)).await;
+            assert!(result.is_err());
+            assert_eq!(calls.load(Ordering::SeqCst), expected_calls);
+        }
     }
 }
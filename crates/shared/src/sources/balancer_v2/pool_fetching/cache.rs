@@ -7,7 +7,14 @@ use {
     super::internal::InternalPoolFetching,
     crate::{
         maintenance::Maintaining,
-        recent_block_cache::{Block, CacheConfig, CacheFetching, CacheKey, RecentBlockCache},
+        recent_block_cache::{
+            Block,
+            CacheConfig,
+            CacheFetching,
+            CacheKey,
+            FetchError,
+            RecentBlockCache,
+        },
         sources::balancer_v2::pools::Pool,
     },
     anyhow::Result,
@@ -99,7 +106,11 @@ impl<Inner> CacheFetching<H256, Pool> for CacheFetcher<Inner>
 where
     Inner: InternalPoolFetching,
 {
-    async fn fetch_values(&self, pool_ids: HashSet<H256>, at_block: Block) -> Result<Vec<Pool>> {
-        self.0.pools_by_id(pool_ids, at_block).await
+    async fn fetch_values(
+        &self,
+        pool_ids: HashSet<H256>,
+        at_block: Block,
+    ) -> Result<Vec<Pool>, FetchError> {
+        Ok(self.0.pools_by_id(pool_ids, at_block).await?)
     }
 }
@@ -1,7 +1,14 @@
 use {
     crate::{
         maintenance::Maintaining,
-        recent_block_cache::{Block, CacheConfig, CacheFetching, CacheKey, RecentBlockCache},
+        recent_block_cache::{
+            Block,
+            CacheConfig,
+            CacheFetching,
+            CacheKey,
+            FetchError,
+            RecentBlockCache,
+        },
         sources::uniswap_v2::pool_fetching::{Pool, PoolFetching},
     },
     anyhow::Result,
@@ -24,8 +31,12 @@ impl CacheKey<Pool> for TokenPair {
 
 #[async_trait::async_trait]
 impl CacheFetching<TokenPair, Pool> for Arc<dyn PoolFetching> {
-    async fn fetch_values(&self, keys: HashSet<TokenPair>, block: Block) -> Result<Vec<Pool>> {
-        self.fetch(keys, block).await
+    async fn fetch_values(
+        &self,
+        keys: HashSet<TokenPair>,
+        block: Block,
+    ) -> Result<Vec<Pool>, FetchError> {
+        Ok(self.fetch(keys, block).await?)
     }
 }
 
@@ -11,6 +11,15 @@ use {
 // api to simulate these call requests applied together one after another.
 // Err if communication with the node failed.
 pub async fn trace_many(requests: Vec<CallRequest>, web3: &Web3) -> Result<Vec<BlockTrace>> {
+    trace_many_at(requests, web3, BlockNumber::Latest).await
+}
+
+// Like `trace_many` but the calls are simulated on top of the given block.
+pub async fn trace_many_at(
+    requests: Vec<CallRequest>,
+    web3: &Web3,
+    block: BlockNumber,
+) -> Result<Vec<BlockTrace>> {
     let transport = web3.transport();
     let requests = requests
         .into_iter()
@@ -21,7 +30,6 @@ pub async fn trace_many(requests: Vec<CallRequest>, web3: &Web3) -> Result<Vec<B
             ])
         })
         .collect::<Result<Vec<_>>>()?;
-    let block = BlockNumber::Latest;
     let params = vec![
         serde_json::to_value(requests)?,
         serde_json::to_value(block)?,
@@ -33,6 +41,17 @@ pub async fn trace_many(requests: Vec<CallRequest>, web3: &Web3) -> Result<Vec<B
     serde_json::from_value(response).context("failed to decode trace_callMany response")
 }
 
+// Whether the error of `trace_many` is caused by the node not supporting the
+// trace_callMany api, which is common for restricted RPC providers.
+pub fn is_method_not_found(err: &anyhow::Error) -> bool {
+    // https://www.jsonrpc.org/specification#error_object
+    const METHOD_NOT_FOUND: i64 = -32601;
+    matches!(
+        err.downcast_ref::<web3::Error>(),
+        Some(web3::Error::Rpc(err)) if err.code.code() == METHOD_NOT_FOUND
+    )
+}
+
 // Check the return value of trace_many for whether all top level transactions
 // succeeded (did not revert).
 // Err if the response is missing trace data.
@@ -55,6 +74,19 @@ pub fn all_calls_succeeded(traces: &[BlockTrace]) -> Result<bool> {
 mod tests {
     use {super::*, serde_json::json};
 
+    #[test]
+    fn detects_unsupported_trace_api() {
+        let rpc_error = |code: i64| {
+            anyhow::Error::new(web3::Error::Rpc(
+                serde_json::from_value(json!({ "code": code, "message": "" })).unwrap(),
+            ))
+            .context("trace_callMany failed")
+        };
+        assert!(is_method_not_found(&rpc_error(-32601)));
+        assert!(!is_method_not_found(&rpc_error(-32000)));
+        assert!(!is_method_not_found(&anyhow::anyhow!("timeout")));
+    }
+
     #[test]
     fn ok_true() {
         let response: Vec<BlockTrace> = serde_json::from_value(json!(
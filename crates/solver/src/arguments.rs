@@ -149,6 +149,44 @@ pub struct Arguments {
     )]
     pub gas_price_cap: f64,
 
+    /// Factor by which the simulated gas estimate of a settlement gets
+    /// multiplied to compute the balance the solver account needs to have
+    /// for the settlement to be considered. Has to be at least 1.
+    #[clap(
+        long,
+        env,
+        default_value_t = crate::settlement_submission::DEFAULT_GAS_LIMIT_FACTOR,
+        value_parser = parse_gas_limit_factor
+    )]
+    pub gas_limit_factor: f64,
+
+    /// Rate settlements without checking that the solver account has enough
+    /// balance to pay for them. Only meant for analyzing settlements on a
+    /// fork where the solver account is not funded.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "false")]
+    pub settlement_rating_dry_run: bool,
+
+    /// Trace simulated settlements to split their gas usage up between the
+    /// pre-, intra- and post-interactions. Tracing is more expensive than only
+    /// estimating the gas of a settlement.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "false")]
+    pub trace_settlement_gas_breakdown: bool,
+
+    /// How often the gas estimation of a settlement gets retried when it fails
+    /// because of a transport error. Reverts are never retried.
+    #[clap(long, env, default_value = "1")]
+    pub settlement_simulation_retries: u32,
+
+    /// The delay in seconds between retries of a failed settlement gas
+    /// estimation.
+    #[clap(
+        long,
+        env,
+        default_value = "0.1",
+        value_parser = shared::arguments::duration_from_seconds,
+    )]
+    pub settlement_simulation_retry_delay: Duration,
+
     /// How to to submit settlement transactions.
     /// Expected to contain either:
     /// 1. One value equal to TransactionStrategyArg::DryRun or
@@ -401,6 +439,27 @@ impl std::fmt::Display for Arguments {
                 .map(|list| format!("{list:?}")),
         )?;
         writeln!(f, "gas_price_cap: {}", self.gas_price_cap)?;
+        writeln!(f, "gas_limit_factor: {}", self.gas_limit_factor)?;
+        writeln!(
+            f,
+            "settlement_rating_dry_run: {}",
+            self.settlement_rating_dry_run
+        )?;
+        writeln!(
+            f,
+            "trace_settlement_gas_breakdown: {}",
+            self.trace_settlement_gas_breakdown
+        )?;
+        writeln!(
+            f,
+            "settlement_simulation_retries: {}",
+            self.settlement_simulation_retries
+        )?;
+        writeln!(
+            f,
+            "settlement_simulation_retry_delay: {:?}",
+            self.settlement_simulation_retry_delay
+        )?;
         writeln!(f, "transaction_strategy: {:?}", self.transaction_strategy)?;
         writeln!(
             f,
@@ -502,6 +561,15 @@ impl std::fmt::Display for Arguments {
     }
 }
 
+fn parse_gas_limit_factor(s: &str) -> anyhow::Result<f64> {
+    let factor = s.parse::<f64>()?;
+    anyhow::ensure!(
+        factor.is_finite() && factor >= 1.,
+        "gas limit factor has to be a finite number of at least 1"
+    );
+    Ok(factor)
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, clap::ValueEnum)]
 #[clap(rename_all = "verbatim")]
 pub enum TransactionStrategyArg {
@@ -510,3 +578,18 @@ pub enum TransactionStrategyArg {
     Flashbots,
     DryRun,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_limit_factor_has_to_be_at_least_one() {
+        assert_eq!(parse_gas_limit_factor("1").unwrap(), 1.);
+        assert_eq!(parse_gas_limit_factor("2.5").unwrap(), 2.5);
+        assert!(parse_gas_limit_factor("0.9").is_err());
+        assert!(parse_gas_limit_factor("-2").is_err());
+        assert!(parse_gas_limit_factor("inf").is_err());
+        assert!(parse_gas_limit_factor("NaN").is_err());
+    }
+}
@@ -2,6 +2,7 @@ use {
     crate::{driver::solver_competition::Score, settlement::Settlement},
     num::BigRational,
     primitive_types::U256,
+    std::cmp::Ordering,
 };
 
 pub fn has_user_order(settlement: &Settlement) -> bool {
@@ -19,9 +20,80 @@ pub struct RatedSettlement {
     pub solver_fees: BigRational, // In wei.
     pub gas_estimate: U256,       // In gas units.
     pub gas_price: BigRational,   // In wei per gas unit.
+    pub solver_balance: U256,     // In wei.
+    pub required_balance: U256,   // In wei. Needed to submit the settlement.
     pub objective_value: BigRational,
     pub score: Score,   // auction based score.
     pub ranking: usize, // auction based ranking.
+    /// Only computed if the settlement rater is configured to trace
+    /// settlements.
+    pub gas_breakdown: Option<GasBreakdown>,
+}
+
+/// How the simulated gas of a settlement is split up between the interaction
+/// stages.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GasBreakdown {
+    pub pre_interactions: U256,
+    pub intra_interactions: U256,
+    pub post_interactions: U256,
+    /// Everything that isn't an interaction, e.g. the intrinsic transaction
+    /// cost, signature verification and token transfers.
+    pub settlement: U256,
+}
+
+/// Rated settlements are ranked by their score. Ties are broken by the
+/// objective value and then by the id, so two settlements only compare as
+/// equal if they are the same settlement of a run loop.
+impl Ord for RatedSettlement {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .score()
+            .cmp(&other.score.score())
+            .then_with(|| self.objective_value.cmp(&other.objective_value))
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for RatedSettlement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for RatedSettlement {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RatedSettlement {}
+
+/// How the objective value of a rated settlement is composed.
+///
+/// `objective_value = surplus + solver_fees - gas_cost`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectiveValueBreakdown {
+    pub surplus: BigRational,     // In wei.
+    pub solver_fees: BigRational, // In wei.
+    pub gas_cost: BigRational,    // In wei.
+    pub objective_value: BigRational,
+}
+
+impl RatedSettlement {
+    /// Breaks the objective value down into its components.
+    ///
+    /// The gas cost is derived from the objective value rather than from
+    /// `gas_estimate`, since the objective may have been computed with a
+    /// solver provided gas amount instead of the simulated one.
+    pub fn objective_value_breakdown(&self) -> ObjectiveValueBreakdown {
+        ObjectiveValueBreakdown {
+            surplus: self.surplus.clone(),
+            solver_fees: self.solver_fees.clone(),
+            gas_cost: &self.surplus + &self.solver_fees - &self.objective_value,
+            objective_value: self.objective_value.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -83,4 +155,52 @@ mod tests {
         ]);
         assert!(has_user_order(&settlement));
     }
+
+    #[test]
+    fn ordered_by_score_then_objective_value_then_id() {
+        let rated = |score: u64, objective_value: i64, id| RatedSettlement {
+            id,
+            score: Score::Solver(score.into()),
+            objective_value: BigRational::from_integer(objective_value.into()),
+            ..Default::default()
+        };
+
+        assert!(rated(2, 0, 0) > rated(1, 10, 1));
+        assert!(rated(1, 10, 0) > rated(1, 5, 1));
+        assert!(rated(1, 5, 1) > rated(1, 5, 0));
+        assert_eq!(rated(1, 5, 0), rated(1, 5, 0));
+
+        let mut settlements = vec![rated(1, 5, 0), rated(3, 0, 1), rated(1, 7, 2)];
+        settlements.sort();
+        let ids: Vec<_> = settlements.iter().map(|settlement| settlement.id).collect();
+        assert_eq!(ids, [0, 2, 1]);
+    }
+
+    #[test]
+    fn objective_value_breakdown_sums_components() {
+        let rational = |value: i64| BigRational::from_integer(value.into());
+        let rated = RatedSettlement {
+            surplus: rational(100),
+            solver_fees: rational(20),
+            gas_estimate: 10.into(),
+            gas_price: rational(3),
+            objective_value: rational(90),
+            ..Default::default()
+        };
+
+        let breakdown = rated.objective_value_breakdown();
+        assert_eq!(
+            breakdown,
+            ObjectiveValueBreakdown {
+                surplus: rational(100),
+                solver_fees: rational(20),
+                gas_cost: rational(30),
+                objective_value: rational(90),
+            }
+        );
+        assert_eq!(
+            breakdown.surplus + breakdown.solver_fees - breakdown.gas_cost,
+            breakdown.objective_value
+        );
+    }
 }
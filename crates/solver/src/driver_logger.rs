@@ -186,6 +186,7 @@ impl DriverLogger {
                             solver, settlement, ..
                         },
                     error: error_at_latest_block,
+                    revert_reason,
                 },
                 result,
             ) in errors.iter().zip(simulations)
@@ -194,6 +195,7 @@ impl DriverLogger {
                     .settlement_simulation(&solver.name, SolverSimulationOutcome::FailureOnLatest);
                 if let Err(error_at_earlier_block) = result {
                     tracing::warn!(
+                        ?revert_reason,
                         "{} settlement simulation failed at submission and block {}:\n{:?}",
                         solver.name,
                         current_block_during_liquidity_fetch,
@@ -290,9 +292,12 @@ mod tests {
                     solver_fees: BigRational::new(3u8.into(), 1u8.into()),
                     gas_estimate: 4.into(),
                     gas_price: BigRational::new(5u8.into(), 1u8.into()),
+                    solver_balance: 100.into(),
+                    required_balance: 20.into(),
                     objective_value: BigRational::new(6u8.into(), 1u8.into()),
                     score: Score::Solver(6.into()),
                     ranking: 1,
+                    gas_breakdown: None,
                 },
             ),
             (
@@ -305,9 +310,12 @@ mod tests {
                     solver_fees: BigRational::new(9u8.into(), 1u8.into()),
                     gas_estimate: 10.into(),
                     gas_price: BigRational::new(11u8.into(), 1u8.into()),
+                    solver_balance: 100.into(),
+                    required_balance: 55.into(),
                     objective_value: BigRational::new(12u8.into(), 1u8.into()),
                     score: Score::Solver(12.into()),
                     ranking: 2,
+                    gas_breakdown: None,
                 },
             ),
         ];
@@ -359,6 +359,11 @@ pub async fn run(args: Arguments) {
             matches!(s, TransactionStrategyArg::PublicMempool)
                 && !args.disable_high_risk_public_mempool_transactions
         }),
+        gas_limit_factor: args.gas_limit_factor,
+        dry_run: args.settlement_rating_dry_run,
+        trace_gas_breakdown: args.trace_settlement_gas_breakdown,
+        simulation_retries: args.settlement_simulation_retries,
+        simulation_retry_delay: args.settlement_simulation_retry_delay,
     });
 
     let solver = crate::solver::create(
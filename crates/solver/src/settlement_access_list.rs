@@ -18,7 +18,7 @@ use {
     },
     web3::{
         helpers,
-        types::{AccessList, AccessListItem, Bytes, CallRequest},
+        types::{AccessList, AccessListItem, BlockNumber, Bytes, CallRequest},
         BatchTransport,
         Transport,
     },
@@ -26,12 +26,15 @@ use {
 
 #[async_trait::async_trait]
 pub trait AccessListEstimating: Send + Sync {
+    /// Estimates the access list on top of `at_block` if it is set and on top
+    /// of the latest block otherwise.
     async fn estimate_access_list(
         &self,
         tx: &TransactionBuilder<DynTransport>,
         partial_access_list: Option<AccessList>,
+        at_block: Option<u64>,
     ) -> Result<AccessList> {
-        self.estimate_access_lists(std::slice::from_ref(tx), partial_access_list)
+        self.estimate_access_lists(std::slice::from_ref(tx), partial_access_list, at_block)
             .await?
             .into_iter()
             .next()
@@ -44,6 +47,7 @@ pub trait AccessListEstimating: Send + Sync {
         &self,
         txs: &[TransactionBuilder<DynTransport>],
         partial_access_list: Option<AccessList>,
+        at_block: Option<u64>,
     ) -> Result<Vec<Result<AccessList>>>;
 }
 
@@ -62,6 +66,9 @@ pub trait AccessListEstimating: Send + Sync {
 /// then simulate the full access list, passing the partial access list into the
 /// simulation. This way the settlement contract does not fail, and hence the
 /// full access list estimation also does not fail.
+///
+/// The access lists are estimated on top of `at_block` if it is set and on
+/// top of the latest block otherwise.
 pub async fn estimate_settlement_access_list(
     estimator: &dyn AccessListEstimating,
     code_fetcher: &dyn CodeFetching,
@@ -69,7 +76,15 @@ pub async fn estimate_settlement_access_list(
     solver_account: Account,
     settlement: &Settlement,
     tx: &TransactionBuilder<DynTransport>,
+    at_block: Option<u64>,
 ) -> Result<AccessList> {
+    let block_number = || async {
+        match at_block {
+            Some(block) => Ok(block),
+            None => Result::<_>::Ok(web3.eth().block_number().await?.as_u64()),
+        }
+    };
+
     // Generate partial access lists for all smart contracts
     let partial_access_lists = try_join_all(settlement.trades().map(|trade| async {
         let buy_token = trade.order.data.buy_token;
@@ -87,14 +102,14 @@ pub async fn estimate_settlement_access_list(
                     .to(receiver)
                     .value(1.into());
                 let simulation_link = tenderly_link(
-                    web3.eth().block_number().await?.as_u64(),
+                    block_number().await?,
                     &web3.net().version().await?,
                     tx.clone(),
                     None,
                     None
                 );
                 tracing::debug!(%simulation_link, ?order_uid, "generating partial access list for trade");
-                estimator.estimate_access_list(&tx, None).await?
+                estimator.estimate_access_list(&tx, None, at_block).await?
             } else {
                 Default::default()
             };
@@ -127,7 +142,7 @@ pub async fn estimate_settlement_access_list(
         .collect_vec();
 
     let simulation_link = tenderly_link(
-        web3.eth().block_number().await?.as_u64(),
+        block_number().await?,
         &web3.net().version().await?,
         tx.clone(),
         None,
@@ -137,7 +152,7 @@ pub async fn estimate_settlement_access_list(
 
     // Generate the final access list
     estimator
-        .estimate_access_list(tx, Some(partial_access_list))
+        .estimate_access_list(tx, Some(partial_access_list), at_block)
         .await
 }
 
@@ -168,6 +183,7 @@ impl AccessListEstimating for NodeAccessList {
         &self,
         txs: &[TransactionBuilder<DynTransport>],
         partial_access_list: Option<AccessList>,
+        at_block: Option<u64>,
     ) -> Result<Vec<Result<AccessList>>> {
         if txs.is_empty() {
             return Ok(Default::default());
@@ -183,11 +199,14 @@ impl AccessListEstimating for NodeAccessList {
                     access_list: partial_access_list.clone(),
                     ..Default::default()
                 };
-                let params = helpers::serialize(&request);
+                let mut params = vec![helpers::serialize(&request)];
+                if let Some(block) = at_block {
+                    params.push(helpers::serialize(&BlockNumber::Number(block.into())));
+                }
                 let (id, request) = self
                     .web3
                     .transport()
-                    .prepare("eth_createAccessList", vec![params]);
+                    .prepare("eth_createAccessList", params);
                 Ok((id, request))
             })
             .collect::<Vec<_>>();
@@ -240,6 +259,7 @@ impl AccessListEstimating for TenderlyAccessList {
         &self,
         txs: &[TransactionBuilder<DynTransport>],
         partial_access_list: Option<AccessList>,
+        at_block: Option<u64>,
     ) -> Result<Vec<Result<AccessList>>> {
         Ok(futures::future::join_all(txs.iter().map(|tx| async {
             let (from, to, input) = resolve_call_request(tx)?;
@@ -250,6 +270,7 @@ impl AccessListEstimating for TenderlyAccessList {
                 from,
                 input,
                 to,
+                block_number: at_block,
                 generate_access_list: Some(true),
                 access_list: partial_access_list.as_ref().map(|access_list| {
                     access_list
@@ -329,10 +350,11 @@ impl AccessListEstimating for PriorityAccessListEstimating {
         &self,
         txs: &[TransactionBuilder<DynTransport>],
         partial_access_list: Option<AccessList>,
+        at_block: Option<u64>,
     ) -> Result<Vec<Result<AccessList>>> {
         for (i, estimator) in self.estimators.iter().enumerate() {
             match estimator
-                .estimate_access_lists(txs, partial_access_list.clone())
+                .estimate_access_lists(txs, partial_access_list.clone(), at_block)
                 .await
             {
                 Ok(result) => {
@@ -390,6 +412,7 @@ mod tests {
     use {
         super::*,
         ethcontract::{Account, H160},
+        ethrpc::mock::MockTransport,
         hex_literal::hex,
         serde_json::json,
         shared::{
@@ -574,12 +597,15 @@ mod tests {
 
         let tx = example_tx();
         let access_lists = tenderly_api
-            .estimate_access_lists(&[tx], None)
+            .estimate_access_lists(&[tx], None, None)
             .await
             .unwrap();
         dbg!(access_lists);
 
-        let access_lists = tenderly_api.estimate_access_lists(&[], None).await.unwrap();
+        let access_lists = tenderly_api
+            .estimate_access_lists(&[], None, None)
+            .await
+            .unwrap();
         dbg!(access_lists);
     }
 
@@ -592,9 +618,15 @@ mod tests {
 
         let tx = example_tx();
 
-        let access_lists = node_api.estimate_access_lists(&[tx], None).await.unwrap();
+        let access_lists = node_api
+            .estimate_access_lists(&[tx], None, None)
+            .await
+            .unwrap();
         dbg!(access_lists);
-        let access_lists = node_api.estimate_access_lists(&[], None).await.unwrap();
+        let access_lists = node_api
+            .estimate_access_lists(&[], None, None)
+            .await
+            .unwrap();
         dbg!(access_lists);
     }
 
@@ -610,12 +642,38 @@ mod tests {
         let tx3 = example_tx();
 
         let access_lists = node_api
-            .estimate_access_lists(&[tx, tx2, tx3], None)
+            .estimate_access_lists(&[tx, tx2, tx3], None, None)
             .await
             .unwrap();
         dbg!(access_lists);
     }
 
+    #[tokio::test]
+    async fn node_estimates_access_lists_at_block() {
+        let transport = MockTransport::new();
+        transport.mock().expect_execute_batch().returning(|calls| {
+            let [(method, params)] = calls.as_slice() else {
+                panic!("unexpected batch {calls:?}");
+            };
+            assert_eq!(method, "eth_createAccessList");
+            assert_eq!(params[1], json!("0x2a"));
+            Ok(vec![Ok(json!({ "accessList": [] }))])
+        });
+        let web3 = Web3::new(DynTransport::new(transport));
+        let node_api = NodeAccessList::new(web3.clone());
+        let tx = TransactionBuilder::new(web3)
+            .from(Account::Local(H160([1; 20]), None))
+            .to(H160([2; 20]))
+            .data(Default::default());
+
+        let access_lists = node_api
+            .estimate_access_lists(&[tx], None, Some(42))
+            .await
+            .unwrap();
+        assert_eq!(access_lists.len(), 1);
+        assert!(access_lists[0].as_ref().unwrap().is_empty());
+    }
+
     #[test]
     fn filter_access_list_node() {
         let access_list = json!(
@@ -153,8 +153,12 @@ impl SettlementRanker {
         gas_price: GasPrice1559,
         auction_id: AuctionId,
     ) -> Result<(Vec<RatedSolverSettlement>, Vec<SimulationWithError>)> {
-        let solver_settlements =
+        let mut solver_settlements =
             self.get_legal_settlements(settlements, external_prices, auction_id);
+        // Shuffle the settlements before they get their ids. Ids break ties in the
+        // ranking so this makes sure we don't give preference to any specific
+        // solver when there is a tie.
+        solver_settlements.shuffle(&mut rand::thread_rng());
 
         // log considered settlements. While we already log all found settlements, this
         // additonal statement allows us to figure out which settlements were
@@ -190,6 +194,7 @@ impl SettlementRanker {
                             external_prices,
                             gas_price,
                             i,
+                            None,
                         )
                         .await;
                     (solver, simulation)
@@ -207,7 +212,10 @@ impl SettlementRanker {
                         AuctionResult::Rejected(SolverRejectionReason::SimulationFailure(
                             TransactionWithError {
                                 transaction: error.simulation.transaction.clone(),
-                                error: error.error.to_string(),
+                                error: match &error.revert_reason {
+                                    Some(reason) => format!("{}: {reason}", error.error),
+                                    None => error.error.to_string(),
+                                },
                             },
                         )),
                     );
@@ -290,10 +298,7 @@ impl SettlementRanker {
             valid_score
         });
 
-        // Before sorting, make sure to shuffle the settlements. This is to make sure we
-        // don't give preference to any specific solver when there is a score tie.
-        rated_settlements.shuffle(&mut rand::thread_rng());
-        rated_settlements.sort_by_key(|(_, settlement)| settlement.score.score());
+        rated_settlements.sort_by(|(_, a), (_, b)| a.cmp(b));
 
         rated_settlements
             .iter_mut()
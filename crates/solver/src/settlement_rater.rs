@@ -1,19 +1,23 @@
 use {
     crate::{
-        driver::solver_settlements::RatedSettlement,
+        driver::solver_settlements::{GasBreakdown, RatedSettlement},
         settlement::Settlement,
         settlement_access_list::{estimate_settlement_access_list, AccessListEstimating},
         settlement_simulation::{
             call_data,
+            revert_reason,
+            settle_call_request,
             settle_method,
+            simulate_and_estimate_gas_at_block,
             simulate_and_estimate_gas_at_current_block,
         },
-        settlement_submission::gas_limit_for_estimate,
+        settlement_submission::gas_limit_for_estimate_with_factor,
         solver::{Simulation, SimulationError, SimulationWithError, SolverInfo},
     },
     anyhow::{anyhow, Context, Result},
     contracts::GPv2Settlement,
-    ethcontract::Account,
+    ethcontract::{errors::ExecutionError, Account},
+    futures::{Future, StreamExt},
     gas_estimation::GasPrice1559,
     model::solver_competition::Score,
     num::{zero, BigRational, CheckedDiv, One},
@@ -27,13 +31,19 @@ use {
             self,
             model::{InternalizationStrategy, SimulatedTransaction},
         },
+        interaction::EncodedInteraction,
+        trace_many,
     },
-    std::{borrow::Borrow, cmp::min, sync::Arc},
-    web3::types::AccessList,
+    std::{borrow::Borrow, cmp::min, sync::Arc, time::Duration},
+    web3::types::{AccessList, Action, BlockNumber, Res, TransactionTrace},
 };
 
 type GasEstimate = U256;
 
+/// How many settlements get rated at the same time by
+/// [`SettlementRating::rate_settlements`].
+const MAX_CONCURRENT_RATINGS: usize = 10;
+
 pub enum SimulateError {
     FailedSimulation(SimulationWithError),
     Internal(anyhow::Error),
@@ -87,7 +97,27 @@ pub trait SettlementRating: Send + Sync {
         prices: &ExternalPrices,
         gas_price: GasPrice1559,
         id: usize,
+        at_block: Option<u64>,
     ) -> Result<RatedSettlement, RatingError>;
+
+    /// Rates multiple settlements of the same solver concurrently at the
+    /// current block. The results are in the same order as the settlements
+    /// and each settlement's index is used as its id.
+    async fn rate_settlements(
+        &self,
+        solver: &SolverInfo,
+        settlements: Vec<Settlement>,
+        prices: &ExternalPrices,
+        gas_price: GasPrice1559,
+    ) -> Vec<Result<RatedSettlement, RatingError>> {
+        futures::stream::iter(settlements.into_iter().enumerate())
+            .map(|(id, settlement)| {
+                self.rate_settlement(solver, settlement, prices, gas_price, id, None)
+            })
+            .buffered(MAX_CONCURRENT_RATINGS)
+            .collect()
+            .await
+    }
 }
 
 pub struct SettlementRater {
@@ -97,6 +127,20 @@ pub struct SettlementRater {
     pub web3: Web3,
     pub score_calculator: ScoreCalculator,
     pub consider_cost_failure: bool,
+    /// Factor applied to the gas estimate to compute the gas limit the solver
+    /// needs to be able to pay for.
+    pub gas_limit_factor: f64,
+    /// Rate settlements without checking that the solver can pay for them.
+    /// Useful for analyzing settlements on a fork with an unfunded solver.
+    pub dry_run: bool,
+    /// Trace the simulated settlement to compute its [`GasBreakdown`]. This is
+    /// more expensive than only estimating the gas.
+    pub trace_gas_breakdown: bool,
+    /// How often a gas estimation that failed because of a transport error
+    /// gets retried. Reverts are never retried.
+    pub simulation_retries: u32,
+    /// How long to wait before retrying a failed gas estimation.
+    pub simulation_retry_delay: Duration,
 }
 
 impl SettlementRater {
@@ -106,6 +150,7 @@ impl SettlementRater {
         settlement: &Settlement,
         gas_price: GasPrice1559,
         internalization: InternalizationStrategy,
+        at_block: Option<u64>,
     ) -> Option<AccessList> {
         let tx = settle_method(
             gas_price,
@@ -121,43 +166,125 @@ impl SettlementRater {
             account.clone(),
             settlement,
             &tx,
+            at_block,
         )
         .await
+        .map_err(|err| {
+            tracing::warn!(
+                ?err,
+                "failed to estimate access list, gas estimate may be less accurate"
+            )
+        })
         .ok()
     }
 
+    /// Traces the simulated settlement to split its gas up between the
+    /// interaction stages. Failures only get logged since the breakdown is
+    /// purely informational.
+    async fn trace_gas_usage(
+        &self,
+        simulation: &Simulation,
+        gas_price: GasPrice1559,
+        gas_estimate: GasEstimate,
+        at_block: Option<u64>,
+    ) -> Option<GasBreakdown> {
+        let settlement = simulation
+            .settlement
+            .clone()
+            .encode(simulation.transaction.internalization);
+        let interactions = settlement.interactions.clone();
+        let request = settle_call_request(
+            &simulation.solver.account,
+            settlement,
+            simulation.transaction.access_list.clone(),
+            &self.settlement_contract,
+            gas_price,
+        );
+        let block = at_block.map_or(BlockNumber::Latest, |block| {
+            BlockNumber::Number(block.into())
+        });
+        let traces = match trace_many::trace_many_at(vec![request], &self.web3, block).await {
+            Ok(traces) => traces,
+            Err(err) if trace_many::is_method_not_found(&err) => {
+                tracing::warn!(
+                    "node does not support trace_callMany, consider disabling the gas breakdown"
+                );
+                return None;
+            }
+            Err(err) => {
+                tracing::warn!(?err, "failed to trace settlement");
+                return None;
+            }
+        };
+        let calls = traces.first()?.trace.as_deref()?;
+        Some(gas_breakdown(&interactions, calls, gas_estimate))
+    }
+
     /// Simulates the settlement and returns the gas used or the reason for a
-    /// revert.
+    /// revert. The simulation happens on top of `at_block` if it is set and on
+    /// top of the current block otherwise.
     async fn simulate_settlement(
         &self,
         solver: &SolverInfo,
         settlement: &Settlement,
         gas_price: GasPrice1559,
         internalization: InternalizationStrategy,
+        at_block: Option<u64>,
     ) -> Result<(Simulation, GasEstimate), SimulateError> {
-        let access_list = self
-            .generate_access_list(&solver.account, settlement, gas_price, internalization)
-            .await;
-        let block_number = self
-            .web3
-            .eth()
-            .block_number()
-            .await
-            .context("failed to get block number")?
-            .as_u64();
-        let simulation_result = simulate_and_estimate_gas_at_current_block(
-            std::iter::once((
-                solver.account.clone(),
-                settlement.clone().encode(internalization),
-                access_list.clone(),
-            )),
-            &self.settlement_contract,
-            gas_price,
+        let block_number = async {
+            match at_block {
+                Some(block) => Ok(block),
+                None => self
+                    .web3
+                    .eth()
+                    .block_number()
+                    .await
+                    .map(|block| block.as_u64()),
+            }
+        };
+        let (access_list, block_number) = futures::join!(
+            self.generate_access_list(
+                &solver.account,
+                settlement,
+                gas_price,
+                internalization,
+                at_block,
+            ),
+            block_number,
+        );
+        let block_number = block_number.context("failed to get block number")?;
+        let estimate_gas = || async {
+            match at_block {
+                Some(block) => Ok(simulate_and_estimate_gas_at_block(
+                    &self.web3,
+                    &solver.account,
+                    settlement.clone().encode(internalization),
+                    access_list.clone(),
+                    &self.settlement_contract,
+                    gas_price,
+                    block,
+                )
+                .await),
+                None => simulate_and_estimate_gas_at_current_block(
+                    std::iter::once((
+                        solver.account.clone(),
+                        settlement.clone().encode(internalization),
+                        access_list.clone(),
+                    )),
+                    &self.settlement_contract,
+                    gas_price,
+                )
+                .await
+                .context("failed to simulate settlements")
+                .map(|mut results| results.pop().expect("yields exactly 1 item")),
+            }
+        };
+        let simulation_result = retry_transport_errors(
+            self.simulation_retries,
+            self.simulation_retry_delay,
+            estimate_gas,
         )
-        .await
-        .context("failed to simulate settlements")?
-        .pop()
-        .expect("yields exactly 1 item");
+        .await?;
 
         let simulation = Simulation {
             transaction: SimulatedTransaction {
@@ -181,6 +308,7 @@ impl SettlementRater {
             Ok(gas_estimate) => Ok((simulation, gas_estimate)),
             Err(error) => Err(SimulateError::FailedSimulation(SimulationWithError {
                 simulation,
+                revert_reason: revert_reason(&error),
                 error: error.into(),
             })),
         }
@@ -196,27 +324,16 @@ impl SettlementRating for SettlementRater {
         prices: &ExternalPrices,
         gas_price: GasPrice1559,
         id: usize,
+        at_block: Option<u64>,
     ) -> Result<RatedSettlement, RatingError> {
-        // first simulate settlements without internalizations to make sure they pass
-        let _ = self
-            .simulate_settlement(
-                solver,
-                &settlement,
-                gas_price,
-                InternalizationStrategy::EncodeAllInteractions,
-            )
-            .await?;
-
-        // since rating is done with internalizations, repeat the simulations for
-        // previously succeeded simulations
-        let (simulation, gas_estimate) = self
-            .simulate_settlement(
-                solver,
-                &settlement,
-                gas_price,
-                InternalizationStrategy::SkipInternalizableInteraction,
-            )
-            .await?;
+        let mut simulation = None;
+        for strategy in simulation_strategies(&settlement) {
+            simulation = Some(
+                self.simulate_settlement(solver, &settlement, gas_price, *strategy, at_block)
+                    .await?,
+            );
+        }
+        let (simulation, gas_estimate) = simulation.expect("at least one simulation strategy");
 
         let effective_gas_price =
             BigRational::from_float(gas_price.effective_gas_price()).expect("Invalid gas price.");
@@ -224,24 +341,41 @@ impl SettlementRating for SettlementRater {
         let solver_balance = self
             .web3
             .eth()
-            .balance(solver.account.address(), None)
+            .balance(
+                solver.account.address(),
+                at_block.map(|block| BlockNumber::Number(block.into())),
+            )
             .await
             .unwrap_or_default();
 
-        let gas_limit = gas_limit_for_estimate(gas_estimate);
+        let gas_limit = gas_limit_for_estimate_with_factor(gas_estimate, self.gas_limit_factor);
         let required_balance =
             gas_limit.saturating_mul(U256::from_f64_lossy(gas_price.max_fee_per_gas));
 
-        if solver_balance < required_balance {
+        if self.dry_run {
+            tracing::warn!(
+                %solver_balance, %required_balance,
+                "dry run, solver balance was not verified"
+            );
+        } else if solver_balance < required_balance {
             return Err(RatingError::FailedSimulation(SimulationWithError {
                 simulation,
                 error: SimulationError::InsufficientBalance {
                     needs: required_balance,
                     has: solver_balance,
                 },
+                revert_reason: None,
             }));
         }
 
+        let gas_breakdown = match self.trace_gas_breakdown {
+            true => {
+                self.trace_gas_usage(&simulation, gas_price, gas_estimate, at_block)
+                    .await
+            }
+            false => None,
+        };
+
         let earned_fees = settlement.total_earned_fees(prices);
         let inputs = {
             let gas_amount = match settlement.score {
@@ -287,14 +421,110 @@ impl SettlementRating for SettlementRater {
             // it's safer and more accurate since simulation gas estimate includes pre/post hooks
             gas_estimate,
             gas_price: effective_gas_price,
+            solver_balance,
+            required_balance,
             objective_value,
             score,
             ranking: Default::default(),
+            gas_breakdown,
         };
         Ok(rated_settlement)
     }
 }
 
+/// Internalization strategies a settlement gets simulated with, in order. The
+/// last simulation is the one used for rating.
+///
+/// Settlements get simulated without internalizations first to make sure they
+/// pass. Since rating is done with internalizations, the simulation is then
+/// repeated with them. Without any internalizable interactions both
+/// simulations would be identical, so only one is done.
+fn simulation_strategies(settlement: &Settlement) -> &'static [InternalizationStrategy] {
+    if settlement.encoder.contains_internalized_interactions() {
+        &[
+            InternalizationStrategy::EncodeAllInteractions,
+            InternalizationStrategy::SkipInternalizableInteraction,
+        ]
+    } else {
+        &[InternalizationStrategy::SkipInternalizableInteraction]
+    }
+}
+
+/// Calls `estimate_gas` until it doesn't fail with a transport error or `retries`
+/// retries have been made. Other errors, like reverts, are returned right away
+/// because retrying them would not change the outcome.
+async fn retry_transport_errors<T, Fut>(
+    retries: u32,
+    delay: Duration,
+    mut estimate_gas: impl FnMut() -> Fut,
+) -> Result<Result<T, ExecutionError>>
+where
+    Fut: Future<Output = Result<Result<T, ExecutionError>>>,
+{
+    let mut attempt = 0;
+    loop {
+        match estimate_gas().await? {
+            Err(err) if attempt < retries && is_transport_error(&err) => {
+                tracing::warn!(
+                    ?err,
+                    attempt,
+                    "retrying gas estimation because of transport error"
+                );
+            }
+            result => return Ok(result),
+        }
+        attempt += 1;
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn is_transport_error(error: &ExecutionError) -> bool {
+    matches!(
+        error,
+        ExecutionError::Web3(web3::Error::Transport(_) | web3::Error::Unreachable)
+    )
+}
+
+/// Attributes the gas used by the direct calls of the settlement contract to
+/// the interaction stages. Calls get matched to the interactions in the order
+/// they are executed; everything else (e.g. signature verification and token
+/// transfers) is attributed to the settlement itself.
+fn gas_breakdown(
+    interactions: &[Vec<EncodedInteraction>; 3],
+    traces: &[TransactionTrace],
+    gas_estimate: U256,
+) -> GasBreakdown {
+    let mut expected = interactions
+        .iter()
+        .enumerate()
+        .flat_map(|(stage, interactions)| interactions.iter().map(move |i| (stage, i)))
+        .peekable();
+    let mut stages = [U256::zero(); 3];
+    for trace in traces.iter().filter(|trace| trace.trace_address.len() == 1) {
+        let (Action::Call(call), Some(Res::Call(result))) = (&trace.action, &trace.result) else {
+            continue;
+        };
+        let Some((stage, (target, value, call_data))) = expected.peek() else {
+            break;
+        };
+        if call.to == *target && call.value == *value && call.input.0 == call_data.0 {
+            stages[*stage] = stages[*stage].saturating_add(result.gas_used);
+            expected.next();
+        }
+    }
+
+    let [pre_interactions, intra_interactions, post_interactions] = stages;
+    let interactions = pre_interactions
+        .saturating_add(intra_interactions)
+        .saturating_add(post_interactions);
+    GasBreakdown {
+        pre_interactions,
+        intra_interactions,
+        post_interactions,
+        settlement: gas_estimate.saturating_sub(interactions),
+    }
+}
+
 #[derive(Debug)]
 pub enum ScoringError {
     ObjectiveValueNonPositive(BigRational),
@@ -519,11 +749,134 @@ fn profit(
 #[cfg(test)]
 mod tests {
     use {
+        super::*,
+        ethcontract::{Bytes, H160},
         num::{BigRational, Zero},
         primitive_types::U256,
         shared::conversions::U256Ext,
+        web3::types::{ActionType, Call, CallResult, CallType},
     };
 
+    #[test]
+    fn simulates_once_without_internalizable_interactions() {
+        let interaction = || Arc::new((H160::zero(), U256::zero(), Bytes::default()));
+
+        let mut settlement = Settlement::new(Default::default());
+        settlement.encoder.append_to_execution_plan(interaction());
+        assert!(matches!(
+            simulation_strategies(&settlement),
+            [InternalizationStrategy::SkipInternalizableInteraction]
+        ));
+
+        settlement
+            .encoder
+            .append_to_execution_plan_internalizable(interaction(), true);
+        assert!(matches!(
+            simulation_strategies(&settlement),
+            [
+                InternalizationStrategy::EncodeAllInteractions,
+                InternalizationStrategy::SkipInternalizableInteraction,
+            ]
+        ));
+    }
+
+    #[test]
+    fn gas_breakdown_by_interaction_stage() {
+        let trace =
+            |trace_address: Vec<usize>, to: u8, input: u8, gas_used: u64| TransactionTrace {
+                trace_address,
+                subtraces: 0,
+                action: Action::Call(Call {
+                    from: H160::zero(),
+                    to: H160([to; 20]),
+                    value: U256::zero(),
+                    gas: U256::zero(),
+                    input: web3::types::Bytes(vec![input]),
+                    call_type: CallType::Call,
+                }),
+                action_type: ActionType::Call,
+                result: Some(Res::Call(CallResult {
+                    gas_used: gas_used.into(),
+                    output: Default::default(),
+                })),
+                error: None,
+            };
+        let interaction =
+            |target: u8, call_data: u8| (H160([target; 20]), U256::zero(), Bytes(vec![call_data]));
+
+        let interactions = [
+            vec![interaction(1, 1)],
+            vec![interaction(2, 2), interaction(2, 3)],
+            vec![],
+        ];
+        let traces = [
+            trace(vec![], 0, 0, 100),
+            trace(vec![0], 1, 1, 10),
+            // nested calls are already included in their parent's gas
+            trace(vec![0, 0], 2, 2, 5),
+            // signature verification and transfers belong to the settlement
+            trace(vec![1], 3, 0, 3),
+            trace(vec![2], 2, 2, 20),
+            trace(vec![3], 2, 3, 30),
+        ];
+
+        assert_eq!(
+            gas_breakdown(&interactions, &traces, 150.into()),
+            GasBreakdown {
+                pre_interactions: 10.into(),
+                intra_interactions: 50.into(),
+                post_interactions: 0.into(),
+                settlement: 90.into(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_gas_estimation_on_transport_errors() {
+        let transport_error = || {
+            ExecutionError::Web3(web3::Error::Transport(
+                web3::error::TransportError::Message("connection reset".into()),
+            ))
+        };
+
+        let mut calls = 0;
+        let result = retry_transport_errors(2, Duration::ZERO, || {
+            calls += 1;
+            let result = match calls {
+                1 => Err(transport_error()),
+                _ => Ok(U256::from(100)),
+            };
+            async move { Ok(result) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(result.unwrap(), 100.into());
+        assert_eq!(calls, 2);
+
+        // reverts are not retried
+        let mut calls = 0;
+        let result = retry_transport_errors(2, Duration::ZERO, || {
+            calls += 1;
+            async { Ok(Err::<U256, _>(ExecutionError::Revert(None))) }
+        })
+        .await
+        .unwrap();
+        assert!(matches!(result, Err(ExecutionError::Revert(None))));
+        assert_eq!(calls, 1);
+
+        // transport errors are returned once the retries are exhausted
+        let mut calls = 0;
+        let result = retry_transport_errors(2, Duration::ZERO, || {
+            calls += 1;
+            let result = Err::<U256, _>(transport_error());
+            async move { Ok(result) }
+        })
+        .await
+        .unwrap();
+        assert!(is_transport_error(&result.unwrap_err()));
+        assert_eq!(calls, 3);
+    }
+
     fn calculate_score(objective_value: &BigRational, success_probability: f64) -> U256 {
         let score_cap = BigRational::from_float(1e16).unwrap();
         let score_calculator = super::ScoreCalculator::new(score_cap);
@@ -559,6 +912,24 @@ mod tests {
         assert_eq!(score, 4999999999999999.);
     }
 
+    #[test]
+    fn compute_score_decreases_with_score_cap() {
+        // a higher cap means a higher penalty in case of reverts, so the optimal
+        // bid can only go down
+        let objective_value = num::BigRational::from_float(1e17).unwrap();
+        let success_probability = 2.0 / 3.0;
+        let scores: Vec<_> = [1e15, 1e16, 5e16, 1e17, 1e18]
+            .into_iter()
+            .map(|score_cap| {
+                super::ScoreCalculator::new(BigRational::from_float(score_cap).unwrap())
+                    .compute_score(&objective_value, BigRational::zero(), success_probability)
+                    .unwrap()
+            })
+            .collect();
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(scores.first().unwrap() > scores.last().unwrap());
+    }
+
     #[test]
     fn compute_score_with_success_probability_one() {
         // if success_probability is 1.0, the score should be equal to the objective
@@ -3,6 +3,7 @@ use {
     contracts::GPv2Settlement,
     ethcontract::{
         batch::CallBatch,
+        common::abi::{self, ParamType},
         contract::MethodBuilder,
         dyns::{DynMethodBuilder, DynTransport},
         errors::ExecutionError,
@@ -12,6 +13,7 @@ use {
     ethrpc::Web3,
     futures::FutureExt,
     gas_estimation::GasPrice1559,
+    hex_literal::hex,
     itertools::Itertools,
     primitive_types::{H160, H256, U256},
     shared::{
@@ -19,7 +21,7 @@ use {
         encoded_settlement::EncodedSettlement,
         tenderly_api::{SimulationRequest, TenderlyApi},
     },
-    web3::types::{AccessList, BlockId},
+    web3::types::{AccessList, BlockId, BlockNumber, Bytes, CallRequest},
 };
 
 const SIMULATE_BATCH_SIZE: usize = 10;
@@ -55,6 +57,24 @@ pub async fn simulate_and_estimate_gas_at_current_block(
     Ok(results)
 }
 
+/// Estimates the gas of a single settlement on top of the state of `block`
+/// instead of the current block.
+pub async fn simulate_and_estimate_gas_at_block(
+    web3: &Web3,
+    account: &Account,
+    settlement: EncodedSettlement,
+    access_list: Option<AccessList>,
+    contract: &GPv2Settlement,
+    gas_price: GasPrice1559,
+    block: u64,
+) -> Result<U256, ExecutionError> {
+    let request = settle_call_request(account, settlement, access_list, contract, gas_price);
+    web3.eth()
+        .estimate_gas(request, Some(BlockNumber::Number(block.into())))
+        .await
+        .map_err(ExecutionError::from)
+}
+
 pub async fn simulate_and_error_with_tenderly_link(
     settlements: impl Iterator<Item = (Account, EncodedSettlement, Option<AccessList>)>,
     contract: &GPv2Settlement,
@@ -180,6 +200,25 @@ pub fn settle_method_builder(
         .from(from)
 }
 
+/// A raw call request of a settle call with this settlement.
+pub fn settle_call_request(
+    account: &Account,
+    settlement: EncodedSettlement,
+    access_list: Option<AccessList>,
+    contract: &GPv2Settlement,
+    gas_price: GasPrice1559,
+) -> CallRequest {
+    CallRequest {
+        from: Some(account.address()),
+        to: Some(contract.address()),
+        data: Some(Bytes(call_data(settlement))),
+        access_list,
+        max_fee_per_gas: Some(U256::from_f64_lossy(gas_price.max_fee_per_gas)),
+        max_priority_fee_per_gas: Some(U256::from_f64_lossy(gas_price.max_priority_fee_per_gas)),
+        ..Default::default()
+    }
+}
+
 /// The call data of a settle call with this settlement.
 pub fn call_data(settlement: EncodedSettlement) -> Vec<u8> {
     let contract = GPv2Settlement::at(&ethrpc::dummy::web3(), H160::default());
@@ -193,6 +232,47 @@ pub fn call_data(settlement: EncodedSettlement) -> Vec<u8> {
     method.tx.data.unwrap().0
 }
 
+/// Tries to extract a human readable revert reason from a failed simulation.
+/// Nodes either decode the reason themselves or return the raw revert data in
+/// the RPC error.
+pub fn revert_reason(error: &ExecutionError) -> Option<String> {
+    match error {
+        ExecutionError::Revert(reason) => reason.clone(),
+        ExecutionError::Web3(web3::Error::Rpc(error)) => {
+            let data = error.data.as_ref()?.as_str()?;
+            let data = hex::decode(data.strip_prefix("0x").unwrap_or(data)).ok()?;
+            decode_revert_data(&data)
+        }
+        _ => None,
+    }
+}
+
+/// Decodes ABI encoded revert data of `Error(string)` and `Panic(uint256)`
+/// reverts. For custom errors only the selector gets reported.
+pub fn decode_revert_data(data: &[u8]) -> Option<String> {
+    // bytes4(keccak256("Error(string)"))
+    const ERROR_SELECTOR: [u8; 4] = hex!("08c379a0");
+    // bytes4(keccak256("Panic(uint256)"))
+    const PANIC_SELECTOR: [u8; 4] = hex!("4e487b71");
+
+    let selector: [u8; 4] = data.get(..4)?.try_into().unwrap();
+    let params = &data[4..];
+    match selector {
+        ERROR_SELECTOR => abi::decode(&[ParamType::String], params)
+            .ok()?
+            .pop()?
+            .into_string(),
+        PANIC_SELECTOR => {
+            let code = abi::decode(&[ParamType::Uint(256)], params)
+                .ok()?
+                .pop()?
+                .into_uint()?;
+            Some(format!("panic code {code:#x}"))
+        }
+        selector => Some(format!("custom error 0x{}", hex::encode(selector))),
+    }
+}
+
 // Creates a simulation link in the gp-v2 tenderly workspace
 pub fn tenderly_link(
     current_block: u64,
@@ -766,6 +846,32 @@ mod tests {
         dbg!(gas_saved);
     }
 
+    #[test]
+    fn decodes_revert_data() {
+        let error = [
+            hex!("08c379a0").as_slice(),
+            &abi::encode(&[abi::Token::String("GPv2: limit price not respected".into())]),
+        ]
+        .concat();
+        assert_eq!(
+            decode_revert_data(&error).unwrap(),
+            "GPv2: limit price not respected"
+        );
+
+        let panic = [
+            hex!("4e487b71").as_slice(),
+            &abi::encode(&[abi::Token::Uint(0x11.into())]),
+        ]
+        .concat();
+        assert_eq!(decode_revert_data(&panic).unwrap(), "panic code 0x11");
+
+        assert_eq!(
+            decode_revert_data(&hex!("deadbeef")).unwrap(),
+            "custom error 0xdeadbeef"
+        );
+        assert_eq!(decode_revert_data(&[]), None);
+    }
+
     #[test]
     fn calldata_works() {
         let settlement = EncodedSettlement::default();
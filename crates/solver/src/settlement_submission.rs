@@ -38,6 +38,10 @@ use {
     web3::types::TransactionReceipt,
 };
 
+/// Factor by which a gas estimate gets multiplied to compute the gas limit
+/// of a settlement transaction.
+pub const DEFAULT_GAS_LIMIT_FACTOR: f64 = 2.0;
+
 /// Computes a gas limit from a gas estimate that accounts for some buffer in
 /// case racing state changes result in slightly more heavy computation at
 /// execution time.
@@ -45,8 +49,12 @@ use {
 /// the end of execution, so we want to increase gas limit enough so those
 /// solutions don't revert with out of gas error.
 pub fn gas_limit_for_estimate(gas_estimate: U256) -> U256 {
-    const GAS_LIMIT_FACTOR: f64 = 2.0;
-    U256::from_f64_lossy(gas_estimate.to_f64_lossy() * GAS_LIMIT_FACTOR)
+    gas_limit_for_estimate_with_factor(gas_estimate, DEFAULT_GAS_LIMIT_FACTOR)
+}
+
+/// Like [`gas_limit_for_estimate`] but with a custom buffer factor.
+pub fn gas_limit_for_estimate_with_factor(gas_estimate: U256, factor: f64) -> U256 {
+    U256::from_f64_lossy(gas_estimate.to_f64_lossy() * factor)
 }
 
 #[derive(Debug)]
@@ -465,6 +473,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn gas_limit_scales_with_factor() {
+        let gas_estimate = U256::from(100_000);
+        assert_eq!(
+            gas_limit_for_estimate_with_factor(gas_estimate, 2.0),
+            U256::from(200_000)
+        );
+        assert_eq!(
+            gas_limit_for_estimate_with_factor(gas_estimate, 1.5),
+            U256::from(150_000)
+        );
+        assert_eq!(
+            gas_limit_for_estimate(gas_estimate),
+            gas_limit_for_estimate_with_factor(gas_estimate, DEFAULT_GAS_LIMIT_FACTOR)
+        );
+    }
+
     #[test]
     fn converts_method_errors() {
         for (from, to) in [
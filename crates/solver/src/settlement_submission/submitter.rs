@@ -562,6 +562,7 @@ impl<'a> Submitter<'a> {
             self.account.clone(),
             settlement,
             tx,
+            None,
         )
         .await?;
         let (without_access_list, with_access_list) = futures::join!(
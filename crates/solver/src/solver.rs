@@ -202,6 +202,8 @@ pub struct Simulation {
 pub struct SimulationWithError {
     pub simulation: Simulation,
     pub error: SimulationError,
+    /// Decoded revert reason if the simulation reverted.
+    pub revert_reason: Option<String>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -305,6 +305,7 @@ impl SingleOrderSolver {
                     max_priority_fee_per_gas: 0.,
                 },
                 id,
+                None,
             )
             .await
             .map_err(|error| anyhow!("rating failed with {:?}", error))?;
@@ -714,7 +715,7 @@ mod tests {
         let mut settlement_rating = MockSettlementRating::new();
         settlement_rating
             .expect_rate_settlement()
-            .returning(|_, _, _, _, _| Ok(Default::default()));
+            .returning(|_, _, _, _, _, _| Ok(Default::default()));
 
         SingleOrderSolver {
             inner: Box::new(inner),
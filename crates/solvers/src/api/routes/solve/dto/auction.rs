@@ -188,7 +188,7 @@ impl ConstantProductPool {
                 .collect_tuple()
                 .ok_or("invalid number of constant product tokens")?;
             liquidity::constant_product::Reserves::new(a, b)
-                .ok_or("invalid constant product pool reserves")?
+                .map_err(|_| "invalid constant product pool reserves")?
         };
 
         Ok(liquidity::Liquidity {
@@ -197,7 +197,9 @@ impl ConstantProductPool {
             gas: eth::Gas(self.gas_estimate),
             state: liquidity::State::ConstantProduct(liquidity::constant_product::Pool {
                 reserves,
-                fee: conv::decimal_to_rational(&self.fee).ok_or("invalid constant product fee")?,
+                fee: conv::decimal_to_rational(&self.fee)
+                    .and_then(|fee| liquidity::constant_product::Fee::new(fee).ok())
+                    .ok_or("invalid constant product fee")?,
             }),
         })
     }
@@ -243,7 +243,7 @@ fn to_boundary_auction(
                     .into_iter()
                     .collect(),
                 }),
-                to_big_rational(&state.fee),
+                to_big_rational(&state.fee.as_rational()),
             ),
             liquidity::State::WeightedProduct(state) => (
                 AmmParameters::WeightedProduct(WeightedProductPoolParameters {
@@ -11,10 +11,11 @@ pub fn to_boundary_pool(address: H160, pool: &liquidity::constant_product::Pool)
     // reserves are ordered by construction.
     let reserves = (reserves.0.amount.as_u128(), reserves.1.amount.as_u128());
 
-    if *pool.fee.numer() > u32::MAX.into() || *pool.fee.denom() > u32::MAX.into() {
+    let fee = pool.fee.as_rational();
+    if *fee.numer() > u32::MAX.into() || *fee.denom() > u32::MAX.into() {
         return None;
     }
-    let fee = num::rational::Ratio::new(pool.fee.numer().as_u32(), pool.fee.denom().as_u32());
+    let fee = num::rational::Ratio::new(fee.numer().as_u32(), fee.denom().as_u32());
 
     Some(Pool {
         address,
@@ -29,7 +29,7 @@ pub struct WethAddress(pub H160);
 
 /// An asset on the Ethereum blockchain. Represents a particular amount of a
 /// particular token.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Asset {
     pub amount: U256,
     pub token: TokenAddress,
@@ -2,15 +2,15 @@
 
 use {
     crate::domain::{eth, liquidity},
-    ethereum_types::U256,
-    std::cmp::Ordering,
+    ethereum_types::{U256, U512},
+    std::{cmp::Ordering, fmt},
 };
 
 /// Uniswap-v2 like pool state.
 #[derive(Clone, Debug)]
 pub struct Pool {
     pub reserves: Reserves,
-    pub fee: eth::Rational,
+    pub fee: Fee,
 }
 
 impl Pool {
@@ -29,6 +29,192 @@ impl Pool {
             .checked_mul(self.reserves.1.amount)
             .expect("product of two u96 cannot overflow a u256")
     }
+
+    /// Computes the output of swapping the specified input asset using the
+    /// Uniswap-v2 swap formula. Returns `None` if the input token is not part
+    /// of the pool, if the pool has no liquidity or on arithmetic overflow.
+    pub fn get_amount_out(&self, input: eth::Asset) -> Option<eth::Asset> {
+        let (reserve_in, reserve_out) = self.relative_reserves(input.token)?;
+        if reserve_in.amount.is_zero() || reserve_out.amount.is_zero() {
+            return None;
+        }
+
+        let fee = self.fee.as_rational();
+        let (fee_numer, fee_denom) = (*fee.numer(), *fee.denom());
+        let amount_in_with_fee = input
+            .amount
+            .checked_mul(fee_denom.checked_sub(fee_numer)?)?;
+        let numerator = amount_in_with_fee.checked_mul(reserve_out.amount)?;
+        let denominator = reserve_in
+            .amount
+            .checked_mul(fee_denom)?
+            .checked_add(amount_in_with_fee)?;
+
+        Some(eth::Asset {
+            token: reserve_out.token,
+            amount: numerator.checked_div(denominator)?,
+        })
+    }
+
+    /// Computes the input required to receive the specified output asset using
+    /// the Uniswap-v2 swap formula. Returns `None` if the output token is not
+    /// part of the pool, if the pool does not have enough liquidity or on
+    /// arithmetic overflow.
+    pub fn get_amount_in(&self, output: eth::Asset) -> Option<eth::Asset> {
+        let (reserve_out, reserve_in) = self.relative_reserves(output.token)?;
+        if reserve_in.amount.is_zero() || output.amount >= reserve_out.amount {
+            return None;
+        }
+
+        let fee = self.fee.as_rational();
+        let (fee_numer, fee_denom) = (*fee.numer(), *fee.denom());
+        let numerator = reserve_in
+            .amount
+            .checked_mul(output.amount)?
+            .checked_mul(fee_denom)?;
+        let denominator =
+            (reserve_out.amount - output.amount).checked_mul(fee_denom.checked_sub(fee_numer)?)?;
+
+        Some(eth::Asset {
+            token: reserve_in.token,
+            amount: numerator.checked_div(denominator)?.checked_add(1.into())?,
+        })
+    }
+
+    /// Returns the state of the pool after swapping the specified input asset.
+    /// The input gets added to the reserves and the output computed with
+    /// [`Self::get_amount_out`] gets removed from them. Returns `None` if the
+    /// swap is not possible or if the resulting reserves would overflow.
+    pub fn after_swap(&self, input: eth::Asset) -> Option<Pool> {
+        let (reserve_in, reserve_out) = self.relative_reserves(input.token)?;
+        let output = self.get_amount_out(input)?;
+        let reserves = Reserves::new(
+            eth::Asset {
+                token: reserve_in.token,
+                amount: reserve_in.amount.checked_add(input.amount)?,
+            },
+            eth::Asset {
+                token: reserve_out.token,
+                amount: reserve_out.amount.checked_sub(output.amount)?,
+            },
+        )
+        .ok()?;
+        Some(Pool {
+            reserves,
+            fee: self.fee,
+        })
+    }
+
+    /// Returns the spot price of the specified base token in terms of the
+    /// other token of the pool, that is, the ratio of the other token's reserve
+    /// to the base token's reserve. Returns `None` if the token is not part of
+    /// the pool or if its reserve is empty.
+    pub fn spot_price(&self, base: eth::TokenAddress) -> Option<eth::Rational> {
+        let (reserve_base, reserve_other) = self.relative_reserves(base)?;
+        if reserve_base.amount.is_zero() {
+            return None;
+        }
+        Some(eth::Rational::new_raw(
+            reserve_other.amount,
+            reserve_base.amount,
+        ))
+    }
+
+    /// Returns the price impact of swapping the specified input asset. This is
+    /// the relative difference between the spot price and the effective price
+    /// of the swap, which includes the pool fee. Returns `None` if the swap is
+    /// not possible.
+    pub fn price_impact(&self, input: eth::Asset) -> Option<eth::Rational> {
+        let (reserve_in, reserve_out) = self.relative_reserves(input.token)?;
+        let output = self.get_amount_out(input)?;
+
+        // impact = 1 - (output / input) / (reserve_out / reserve_in)
+        //        = (input * reserve_out - output * reserve_in) / (input * reserve_out)
+        let denominator = input.amount.checked_mul(reserve_out.amount)?;
+        let numerator = denominator.checked_sub(output.amount.checked_mul(reserve_in.amount)?)?;
+        if denominator.is_zero() {
+            return None;
+        }
+        Some(eth::Rational::new_raw(numerator, denominator))
+    }
+
+    /// Returns the pool fee in basis points, rounded to the nearest whole basis
+    /// point.
+    pub fn fee_bps(&self) -> u32 {
+        self.fee.rounded_bps()
+    }
+
+    /// Returns the reserve of the specified token followed by the reserve of
+    /// the other token of the pool, or `None` if the token is not part of the
+    /// pool.
+    fn relative_reserves(&self, token: eth::TokenAddress) -> Option<(eth::Asset, eth::Asset)> {
+        let (a, b) = self.reserves.get();
+        if token == a.token {
+            Some((a, b))
+        } else if token == b.token {
+            Some((b, a))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, b) = self.reserves.get();
+        write!(
+            f,
+            "{:?}: {}, {:?}: {} ({} bps)",
+            a.token.0,
+            a.amount,
+            b.token.0,
+            b.amount,
+            self.fee_bps()
+        )
+    }
+}
+
+/// A multi-hop route through constant product pools.
+#[derive(Clone, Debug)]
+pub struct Route {
+    path: Vec<eth::TokenAddress>,
+    pools: Vec<Pool>,
+}
+
+impl Route {
+    /// Creates a new route that swaps along the specified token path, where
+    /// each pool trades between two consecutive tokens of the path. Returns an
+    /// error if the route is empty or if a pool doesn't trade the tokens of
+    /// its hop.
+    pub fn new(path: Vec<eth::TokenAddress>, pools: Vec<Pool>) -> Result<Self, InvalidRoute> {
+        if pools.is_empty() || path.len() != pools.len() + 1 {
+            return Err(InvalidRoute::Length);
+        }
+        for (hop, pool) in path.windows(2).zip(&pools) {
+            match pool.relative_reserves(hop[0]) {
+                Some((_, out)) if out.token == hop[1] => {}
+                _ => return Err(InvalidRoute::Disconnected(hop[0], hop[1])),
+            }
+        }
+        Ok(Self { path, pools })
+    }
+
+    /// Returns the token path of the route.
+    pub fn path(&self) -> &[eth::TokenAddress] {
+        &self.path
+    }
+
+    /// Computes the output of swapping the specified input asset along the
+    /// route, feeding the output of each hop into the next one. Returns `None`
+    /// if the input isn't the first token of the path or if any hop fails.
+    pub fn get_amount_out(&self, input: eth::Asset) -> Option<eth::Asset> {
+        if input.token != self.path[0] {
+            return None;
+        }
+        self.pools
+            .iter()
+            .try_fold(input, |asset, pool| pool.get_amount_out(asset))
+    }
 }
 
 /// Constant product pool reserves.
@@ -37,20 +223,24 @@ pub struct Reserves(eth::Asset, eth::Asset);
 
 impl Reserves {
     /// Creates a new constant product pool reserves with the specified assets.
-    /// Returns `None` if the assets are denominated in the same token or if the
-    /// balances are larger than the maximum allowed values.
-    pub fn new(a: eth::Asset, b: eth::Asset) -> Option<Self> {
+    /// The assets get ordered by their token address. Returns an error if the
+    /// assets are denominated in the same token or if the balances are larger
+    /// than the maximum allowed values.
+    ///
+    /// Note that empty reserves are allowed since they are valid pool states;
+    /// the swap math simply doesn't produce any amounts for them.
+    pub fn new(a: eth::Asset, b: eth::Asset) -> Result<Self, InvalidReserves> {
         // UniswapV2-Like constant product pools are limited to uint112 values
         // for token reserves - so verify this invariant.
         let max = U256::from(2_u128.pow(112) - 1);
         if a.amount > max || b.amount > max {
-            return None;
+            return Err(InvalidReserves::Overflow);
         }
 
         match a.token.cmp(&b.token) {
-            Ordering::Less => Some(Self(a, b)),
-            Ordering::Equal => None,
-            Ordering::Greater => Some(Self(b, a)),
+            Ordering::Less => Ok(Self(a, b)),
+            Ordering::Equal => Err(InvalidReserves::SameToken),
+            Ordering::Greater => Ok(Self(b, a)),
         }
     }
 
@@ -59,3 +249,315 @@ impl Reserves {
         (self.0, self.1)
     }
 }
+
+/// A constant product pool fee. This is the fraction of the input amount that
+/// is kept by the pool when swapping.
+#[derive(Clone, Copy, Debug)]
+pub struct Fee(eth::Rational);
+
+impl Fee {
+    /// The number of basis points in 100%.
+    const MAX_BPS: u16 = 10_000;
+
+    /// Creates a new fee from the specified fraction. Returns an error if the
+    /// fraction is not between 0 and 1.
+    pub fn new(value: eth::Rational) -> Result<Self, InvalidFee> {
+        if value.denom().is_zero() || value.numer() > value.denom() {
+            return Err(InvalidFee);
+        }
+        Ok(Self(value))
+    }
+
+    /// Creates a new fee from the specified basis points. Returns an error if
+    /// the fee is larger than 10000 basis points.
+    pub fn from_bps(bps: u16) -> Result<Self, InvalidFee> {
+        if bps > Self::MAX_BPS {
+            return Err(InvalidFee);
+        }
+        Ok(Self(eth::Rational::new_raw(
+            bps.into(),
+            Self::MAX_BPS.into(),
+        )))
+    }
+
+    /// Returns the fee as a fraction.
+    pub fn as_rational(&self) -> eth::Rational {
+        self.0
+    }
+
+    /// Returns the fee in basis points. Returns `None` if the fee is not a
+    /// whole number of basis points.
+    pub fn bps(&self) -> Option<u16> {
+        let scaled = self.0.numer().checked_mul(Self::MAX_BPS.into())?;
+        if !(scaled % self.0.denom()).is_zero() {
+            return None;
+        }
+        u16::try_from((scaled / self.0.denom()).as_u32()).ok()
+    }
+
+    /// Returns the fee in basis points, rounded to the nearest whole basis
+    /// point.
+    pub fn rounded_bps(&self) -> u32 {
+        let (numer, denom) = (*self.0.numer(), *self.0.denom());
+        let denom = U512::from(denom);
+        let scaled = numer.full_mul(Self::MAX_BPS.into()) + denom / 2;
+        // The fee is at most 100% so this always fits.
+        (scaled / denom).low_u32()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("fee must be between 0 and 100%")]
+pub struct InvalidFee;
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum InvalidRoute {
+    #[error("route must have one more token than pools and at least one pool")]
+    Length,
+    #[error("no pool between {0:?} and {1:?}")]
+    Disconnected(eth::TokenAddress, eth::TokenAddress),
+}
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum InvalidReserves {
+    #[error("reserve assets are denominated in the same token")]
+    SameToken,
+    #[error("reserve balance exceeds uint112")]
+    Overflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ethereum_types::H160};
+
+    fn pool_with_reserves(reserve_a: u128, reserve_b: u128) -> Pool {
+        Pool {
+            reserves: Reserves::new(
+                eth::Asset {
+                    token: eth::TokenAddress(H160([1; 20])),
+                    amount: reserve_a.into(),
+                },
+                eth::Asset {
+                    token: eth::TokenAddress(H160([2; 20])),
+                    amount: reserve_b.into(),
+                },
+            )
+            .unwrap(),
+            fee: Fee::from_bps(30).unwrap(),
+        }
+    }
+
+    fn asset(token: u8, amount: u128) -> eth::Asset {
+        eth::Asset {
+            token: eth::TokenAddress(H160([token; 20])),
+            amount: amount.into(),
+        }
+    }
+
+    #[test]
+    fn computes_amount_out() {
+        let pool = pool_with_reserves(10_000, 10_000);
+        assert_eq!(pool.get_amount_out(asset(1, 1000)), Some(asset(2, 906)));
+        assert_eq!(pool.get_amount_out(asset(2, 1000)), Some(asset(1, 906)));
+
+        // Values computed with `UniswapV2Library.getAmountOut`.
+        let pool = pool_with_reserves(100_000_000, 50_000);
+        assert_eq!(
+            pool.get_amount_out(asset(1, 1_000_000)),
+            Some(asset(2, 493))
+        );
+        assert_eq!(
+            pool.get_amount_out(asset(2, 1_000)),
+            Some(asset(1, 1_955_016))
+        );
+    }
+
+    #[test]
+    fn computes_amount_in() {
+        let pool = pool_with_reserves(10_000, 10_000);
+        assert_eq!(pool.get_amount_in(asset(2, 906)), Some(asset(1, 1000)));
+        assert_eq!(pool.get_amount_in(asset(1, 906)), Some(asset(2, 1000)));
+
+        // Values computed with `UniswapV2Library.getAmountIn`.
+        let pool = pool_with_reserves(100_000_000, 50_000);
+        assert_eq!(pool.get_amount_in(asset(2, 493)), Some(asset(1, 998_816)));
+    }
+
+    #[test]
+    fn computes_k() {
+        let pool = pool_with_reserves(10_000, 20_000);
+        assert_eq!(pool.k(), 200_000_000.into());
+
+        // Reserves are bounded to uint112, so even the largest reserves can't
+        // overflow.
+        let max = 2_u128.pow(112) - 1;
+        let pool = pool_with_reserves(max, max);
+        assert_eq!(pool.k(), U256::from(max) * U256::from(max));
+    }
+
+    #[test]
+    fn updates_reserves_after_swap() {
+        let pool = pool_with_reserves(10_000, 10_000);
+        let after = pool.after_swap(asset(1, 1000)).unwrap();
+        assert_eq!(after.reserves.get(), (asset(1, 11_000), asset(2, 9_094)));
+        // Fees stay in the pool, so the invariant grows.
+        assert!(after.k() >= pool.k());
+        // The original pool is unchanged.
+        assert_eq!(pool.reserves.get(), (asset(1, 10_000), asset(2, 10_000)));
+
+        let after = pool.after_swap(asset(2, 1000)).unwrap();
+        assert_eq!(after.reserves.get(), (asset(1, 9_094), asset(2, 11_000)));
+        assert!(after.k() >= pool.k());
+
+        assert!(pool.after_swap(asset(3, 1000)).is_none());
+        let full = pool_with_reserves(2_u128.pow(112) - 1, 10_000);
+        assert!(full.after_swap(asset(1, 1000)).is_none());
+    }
+
+    #[test]
+    fn computes_spot_price() {
+        let pool = pool_with_reserves(10_000, 20_000);
+        let price = pool.spot_price(asset(1, 0).token).unwrap();
+        assert_eq!(
+            (*price.numer(), *price.denom()),
+            (20_000.into(), 10_000.into())
+        );
+        let price = pool.spot_price(asset(2, 0).token).unwrap();
+        assert_eq!(
+            (*price.numer(), *price.denom()),
+            (10_000.into(), 20_000.into())
+        );
+        assert!(pool.spot_price(asset(3, 0).token).is_none());
+    }
+
+    #[test]
+    fn computes_price_impact() {
+        let pool = pool_with_reserves(10_000, 10_000);
+
+        // 1000 in for 906 out: (1000 * 10000 - 906 * 10000) / (1000 * 10000)
+        let impact = pool.price_impact(asset(1, 1000)).unwrap();
+        assert_eq!(
+            (*impact.numer(), *impact.denom()),
+            (940_000.into(), 10_000_000.into())
+        );
+
+        // Larger trades have a larger price impact.
+        let larger = pool.price_impact(asset(1, 2000)).unwrap();
+        assert!(
+            larger.numer().full_mul(*impact.denom()) > impact.numer().full_mul(*larger.denom())
+        );
+
+        assert!(pool.price_impact(asset(3, 1000)).is_none());
+    }
+
+    #[test]
+    fn validates_fees() {
+        assert_eq!(Fee::from_bps(30).unwrap().bps(), Some(30));
+        assert_eq!(Fee::from_bps(10_000).unwrap().bps(), Some(10_000));
+        assert!(Fee::from_bps(10_001).is_err());
+
+        let fee = Fee::new(eth::Rational::new_raw(3.into(), 1000.into())).unwrap();
+        assert_eq!(fee.bps(), Some(30));
+        let fee = Fee::new(eth::Rational::new_raw(1.into(), 3.into())).unwrap();
+        assert_eq!(fee.bps(), None);
+        assert!(Fee::new(eth::Rational::new_raw(2.into(), 1.into())).is_err());
+        assert!(Fee::new(eth::Rational::new_raw(0.into(), 0.into())).is_err());
+    }
+
+    #[test]
+    fn rounds_fees_to_nearest_bps() {
+        let fee = |numer: u64, denom: u64| {
+            Fee::new(eth::Rational::new_raw(numer.into(), denom.into())).unwrap()
+        };
+        assert_eq!(fee(3, 1000).rounded_bps(), 30);
+        assert_eq!(fee(1, 3).rounded_bps(), 3333);
+        assert_eq!(fee(2, 3).rounded_bps(), 6667);
+        assert_eq!(fee(1, 20_000).rounded_bps(), 1);
+        assert_eq!(fee(1, 1).rounded_bps(), 10_000);
+        assert_eq!(fee(0, 1).rounded_bps(), 0);
+    }
+
+    #[test]
+    fn displays_reserves_and_fee() {
+        let pool = pool_with_reserves(10_000, 20_000);
+        assert_eq!(pool.fee_bps(), 30);
+        assert_eq!(
+            pool.to_string(),
+            format!(
+                "{:?}: 10000, {:?}: 20000 (30 bps)",
+                H160([1; 20]),
+                H160([2; 20])
+            )
+        );
+    }
+
+    #[test]
+    fn computes_multi_hop_amount_out() {
+        let pool = |a: u8, b: u8| Pool {
+            reserves: Reserves::new(asset(a, 10_000), asset(b, 10_000)).unwrap(),
+            fee: Fee::from_bps(30).unwrap(),
+        };
+        let token = |token: u8| asset(token, 0).token;
+
+        let route = Route::new(
+            vec![token(1), token(2), token(3)],
+            vec![pool(1, 2), pool(2, 3)],
+        )
+        .unwrap();
+        // The first hop yields 906 of token 2, which the second hop swaps
+        // for token 3.
+        assert_eq!(
+            route.get_amount_out(asset(1, 1000)),
+            pool(2, 3).get_amount_out(asset(2, 906))
+        );
+        assert_eq!(route.get_amount_out(asset(1, 1000)), Some(asset(3, 828)));
+        assert_eq!(route.get_amount_out(asset(2, 1000)), None);
+
+        assert_eq!(
+            Route::new(
+                vec![token(1), token(2), token(3)],
+                vec![pool(1, 2), pool(1, 3)]
+            )
+            .unwrap_err(),
+            InvalidRoute::Disconnected(token(2), token(3))
+        );
+        assert_eq!(
+            Route::new(vec![token(1), token(2)], vec![pool(1, 2), pool(2, 3)]).unwrap_err(),
+            InvalidRoute::Length
+        );
+        assert_eq!(
+            Route::new(vec![token(1)], vec![]).unwrap_err(),
+            InvalidRoute::Length
+        );
+    }
+
+    #[test]
+    fn orders_reserves_by_token() {
+        let reserves = Reserves::new(asset(2, 1), asset(1, 2)).unwrap();
+        assert_eq!(reserves.get(), (asset(1, 2), asset(2, 1)));
+    }
+
+    #[test]
+    fn rejects_invalid_reserves() {
+        assert_eq!(
+            Reserves::new(asset(1, 1), asset(1, 2)).unwrap_err(),
+            InvalidReserves::SameToken
+        );
+        assert_eq!(
+            Reserves::new(asset(1, 1 << 112), asset(2, 1)).unwrap_err(),
+            InvalidReserves::Overflow
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_swaps() {
+        let pool = pool_with_reserves(10_000, 10_000);
+        assert_eq!(pool.get_amount_out(asset(3, 1000)), None);
+        assert_eq!(pool.get_amount_in(asset(3, 1000)), None);
+        assert_eq!(pool.get_amount_in(asset(2, 10_000)), None);
+
+        let pool = pool_with_reserves(0, 10_000);
+        assert_eq!(pool.get_amount_out(asset(1, 1000)), None);
+        assert_eq!(pool.get_amount_in(asset(2, 1000)), None);
+    }
+}
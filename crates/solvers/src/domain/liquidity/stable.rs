@@ -1,5 +1,6 @@
 use {
     crate::domain::{eth, liquidity},
+    ethereum_types::U256,
     itertools::Itertools as _,
 };
 
@@ -11,6 +12,208 @@ pub struct Pool {
     pub fee: eth::Rational,
 }
 
+/// The maximum number of Newton iterations used for solving the StableSwap
+/// invariant.
+const MAX_ITERATIONS: usize = 255;
+
+impl Pool {
+    /// Computes the amount of the output token received for swapping the
+    /// specified input asset using the StableSwap invariant. Like in Curve
+    /// pools, the fee is charged on the output amount. Returns `None` if either
+    /// token is not part of the pool or the swap is not possible.
+    pub fn get_amount_out(
+        &self,
+        output: eth::TokenAddress,
+        input: eth::Asset,
+    ) -> Option<eth::Asset> {
+        let (i, j) = (self.position(input.token)?, self.position(output)?);
+        if i == j {
+            return None;
+        }
+
+        let balances = self.normalized_balances()?;
+        let d = invariant(&balances, &self.amplification_parameter)?;
+        let x = balances[i].checked_add(scale(input.amount, &self.reserves.0[i].scale)?)?;
+        let y = solve_balance(&balances, &self.amplification_parameter, d, (i, x), j)?;
+
+        // Round down by 1 in favour of the pool like Curve does.
+        let dy = balances[j].checked_sub(y)?.checked_sub(1.into())?;
+        let fee = dy
+            .checked_mul(*self.fee.numer())?
+            .checked_div(*self.fee.denom())?;
+
+        Some(eth::Asset {
+            token: output,
+            amount: unscale(dy.checked_sub(fee)?, &self.reserves.0[j].scale, false)?,
+        })
+    }
+
+    /// Computes the amount of the input token required for receiving the
+    /// specified output asset using the StableSwap invariant. Returns `None` if
+    /// either token is not part of the pool or the swap is not possible.
+    pub fn get_amount_in(
+        &self,
+        input: eth::TokenAddress,
+        output: eth::Asset,
+    ) -> Option<eth::Asset> {
+        let (i, j) = (self.position(input)?, self.position(output.token)?);
+        if i == j {
+            return None;
+        }
+
+        let balances = self.normalized_balances()?;
+        let d = invariant(&balances, &self.amplification_parameter)?;
+
+        // Gross up the output amount by the fee that gets charged on it.
+        let dy = scale(output.amount, &self.reserves.0[j].scale)?;
+        let dy = ceil_div(
+            dy.checked_mul(*self.fee.denom())?,
+            self.fee.denom().checked_sub(*self.fee.numer())?,
+        )?;
+        let y = balances[j].checked_sub(dy)?;
+        if y.is_zero() {
+            return None;
+        }
+        let x = solve_balance(&balances, &self.amplification_parameter, d, (j, y), i)?;
+        let dx = x.checked_sub(balances[i])?.checked_add(1.into())?;
+
+        Some(eth::Asset {
+            token: input,
+            amount: unscale(dx, &self.reserves.0[i].scale, true)?,
+        })
+    }
+
+    fn position(&self, token: eth::TokenAddress) -> Option<usize> {
+        self.reserves
+            .0
+            .iter()
+            .position(|reserve| reserve.asset.token == token)
+    }
+
+    fn normalized_balances(&self) -> Option<Vec<U256>> {
+        self.reserves
+            .0
+            .iter()
+            .map(|reserve| scale(reserve.asset.amount, &reserve.scale))
+            .collect()
+    }
+}
+
+/// Computes the StableSwap invariant `D` for the specified normalized
+/// balances with Newton's method.
+fn invariant(balances: &[U256], amplification: &eth::Rational) -> Option<U256> {
+    let n = U256::from(balances.len());
+    let sum = balances
+        .iter()
+        .try_fold(U256::zero(), |sum, balance| sum.checked_add(*balance))?;
+    if sum.is_zero() {
+        return Some(U256::zero());
+    }
+
+    // The amplification coefficient multiplied by the number of tokens, which
+    // is kept as a fraction to not lose precision.
+    let (ann, precision) = (
+        amplification.numer().checked_mul(n)?,
+        *amplification.denom(),
+    );
+
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let d_p = balances.iter().try_fold(d, |d_p, balance| {
+            d_p.checked_mul(d)?.checked_div(balance.checked_mul(n)?)
+        })?;
+        let previous = d;
+        let numerator = ann
+            .checked_mul(sum)?
+            .checked_div(precision)?
+            .checked_add(d_p.checked_mul(n)?)?
+            .checked_mul(d)?;
+        let denominator = ann
+            .checked_sub(precision)?
+            .checked_mul(d)?
+            .checked_div(precision)?
+            .checked_add(n.checked_add(1.into())?.checked_mul(d_p)?)?;
+        d = numerator.checked_div(denominator)?;
+        if converged(d, previous) {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// Computes the normalized balance of token `j` that keeps the invariant `d`
+/// when the normalized balance of token `i` changes to `x`.
+fn solve_balance(
+    balances: &[U256],
+    amplification: &eth::Rational,
+    d: U256,
+    (i, x): (usize, U256),
+    j: usize,
+) -> Option<U256> {
+    let n = U256::from(balances.len());
+    let (ann, precision) = (
+        amplification.numer().checked_mul(n)?,
+        *amplification.denom(),
+    );
+
+    let mut c = d;
+    let mut sum = U256::zero();
+    for (k, balance) in balances.iter().enumerate() {
+        let balance = if k == i {
+            x
+        } else if k == j {
+            continue;
+        } else {
+            *balance
+        };
+        sum = sum.checked_add(balance)?;
+        c = c.checked_mul(d)?.checked_div(balance.checked_mul(n)?)?;
+    }
+    let c = c
+        .checked_mul(d)?
+        .checked_mul(precision)?
+        .checked_div(ann.checked_mul(n)?)?;
+    let b = sum.checked_add(d.checked_mul(precision)?.checked_div(ann)?)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let previous = y;
+        let numerator = y.checked_mul(y)?.checked_add(c)?;
+        let denominator = y.checked_mul(2.into())?.checked_add(b)?.checked_sub(d)?;
+        y = numerator.checked_div(denominator)?;
+        if converged(y, previous) {
+            return Some(y);
+        }
+    }
+    None
+}
+
+fn converged(a: U256, b: U256) -> bool {
+    let difference = if a > b { a - b } else { b - a };
+    difference <= 1.into()
+}
+
+fn scale(amount: U256, factor: &liquidity::ScalingFactor) -> Option<U256> {
+    let factor = factor.get();
+    amount
+        .checked_mul(*factor.numer())?
+        .checked_div(*factor.denom())
+}
+
+fn unscale(amount: U256, factor: &liquidity::ScalingFactor, round_up: bool) -> Option<U256> {
+    let factor = factor.get();
+    let amount = amount.checked_mul(*factor.denom())?;
+    if round_up {
+        ceil_div(amount, *factor.numer())
+    } else {
+        amount.checked_div(*factor.numer())
+    }
+}
+
+fn ceil_div(a: U256, b: U256) -> Option<U256> {
+    a.checked_add(b.checked_sub(1.into())?)?.checked_div(b)
+}
+
 /// A reprensentation of BalancerV2-like weighted pool reserves.
 #[derive(Clone, Debug)]
 pub struct Reserves(Vec<Reserve>);
@@ -57,3 +260,83 @@ pub struct Reserve {
     pub asset: eth::Asset,
     pub scale: liquidity::ScalingFactor,
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ethereum_types::H160};
+
+    const DAI: eth::TokenAddress = eth::TokenAddress(H160([1; 20]));
+    const USDC: eth::TokenAddress = eth::TokenAddress(H160([2; 20]));
+
+    fn pool(dai: u128, usdc: u128) -> Pool {
+        let reserve = |token, amount: u128, scale: u128| Reserve {
+            asset: eth::Asset {
+                token,
+                amount: amount.into(),
+            },
+            scale: liquidity::ScalingFactor::new(eth::Rational::new_raw(scale.into(), 1.into()))
+                .unwrap(),
+        };
+        Pool {
+            reserves: Reserves::new(vec![
+                reserve(DAI, dai * 10_u128.pow(18), 1),
+                reserve(USDC, usdc * 10_u128.pow(6), 10_u128.pow(12)),
+            ])
+            .unwrap(),
+            amplification_parameter: eth::Rational::new_raw(100.into(), 1.into()),
+            fee: eth::Rational::new_raw(4.into(), 10_000.into()),
+        }
+    }
+
+    fn asset(token: eth::TokenAddress, amount: u128) -> eth::Asset {
+        eth::Asset {
+            token,
+            amount: amount.into(),
+        }
+    }
+
+    // The expected values were computed with a reference implementation of
+    // the Curve StableSwap `get_D`, `get_y`, `get_dy` and `get_dx` functions.
+
+    #[test]
+    fn swaps_at_peg() {
+        let pool = pool(1_000_000, 1_000_000);
+        assert_eq!(
+            pool.get_amount_out(USDC, asset(DAI, 1_000 * 10_u128.pow(18))),
+            Some(asset(USDC, 999_590_103))
+        );
+        assert_eq!(
+            pool.get_amount_in(DAI, asset(USDC, 999_590_103)),
+            Some(asset(DAI, 999_999_999_941_390_683_925))
+        );
+    }
+
+    #[test]
+    fn swaps_away_from_peg() {
+        let pool = pool(1_500_000, 500_000);
+        assert_eq!(
+            pool.get_amount_out(USDC, asset(DAI, 1_000 * 10_u128.pow(18))),
+            Some(asset(USDC, 982_335_043))
+        );
+        assert_eq!(
+            pool.get_amount_out(DAI, asset(USDC, 1_000 * 10_u128.pow(6))),
+            Some(asset(DAI, 1_017_087_505_670_087_555_363))
+        );
+        assert_eq!(
+            pool.get_amount_in(USDC, asset(DAI, 1_000 * 10_u128.pow(18))),
+            Some(asset(USDC, 983_198_912))
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_swaps() {
+        let pool = pool(1_000_000, 1_000_000);
+        let other = eth::TokenAddress(H160([3; 20]));
+        assert_eq!(pool.get_amount_out(other, asset(DAI, 1_000)), None);
+        assert_eq!(pool.get_amount_out(DAI, asset(DAI, 1_000)), None);
+        assert_eq!(
+            pool.get_amount_in(DAI, asset(USDC, 1_000_000 * 10_u128.pow(6))),
+            None
+        );
+    }
+}
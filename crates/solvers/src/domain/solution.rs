@@ -231,8 +231,26 @@ impl ClearingPrices {
     pub fn new(prices: impl IntoIterator<Item = (eth::TokenAddress, U256)>) -> Self {
         Self(prices.into_iter().collect())
     }
+
+    /// Creates a new set of clearing prices from assets, where each asset's
+    /// amount is the price of its token. Tokens may appear multiple times as
+    /// long as their prices agree.
+    pub fn from_assets(assets: &[eth::Asset]) -> Result<Self, ConflictingPrices> {
+        let mut prices = HashMap::with_capacity(assets.len());
+        for asset in assets {
+            match prices.insert(asset.token, asset.amount) {
+                Some(price) if price != asset.amount => return Err(ConflictingPrices(asset.token)),
+                _ => {}
+            }
+        }
+        Ok(Self(prices))
+    }
 }
 
+#[derive(Debug, thiserror::Error)]
+#[error("conflicting clearing prices for token {0:?}")]
+pub struct ConflictingPrices(pub eth::TokenAddress);
+
 /// A trade which executes an order as part of this solution.
 #[derive(Debug)]
 pub enum Trade {
@@ -429,3 +447,28 @@ pub const SETTLEMENT: u64 = 7365;
 /// Value was computed by taking 52 percentile median of `transfer()` costs
 /// of the 90% most traded tokens by volume in the month of Oct. 2021.
 pub const ERC20_TRANSFER: u64 = 27_513;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clearing_prices_from_assets() {
+        let asset = |token: u64, amount: u64| eth::Asset {
+            token: eth::TokenAddress(Address::from_low_u64_be(token)),
+            amount: amount.into(),
+        };
+
+        let prices =
+            ClearingPrices::from_assets(&[asset(1, 10), asset(2, 20), asset(1, 10)]).unwrap();
+        assert_eq!(
+            prices.0,
+            HashMap::from([
+                (eth::TokenAddress(Address::from_low_u64_be(1)), 10.into()),
+                (eth::TokenAddress(Address::from_low_u64_be(2)), 20.into()),
+            ])
+        );
+
+        assert!(ClearingPrices::from_assets(&[asset(1, 10), asset(1, 11)]).is_err());
+    }
+}